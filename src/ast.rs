@@ -1,3 +1,9 @@
+use std::fmt::Display;
+
+use rstest::rstest;
+
+use crate::token::{Token, char_literal};
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Node {
     // Program variants
@@ -9,16 +15,19 @@ pub enum Node {
     IntegerLiteral {
         value: i64,
     },
+    CharLiteral {
+        value: char,
+    },
     Identifier {
         name: String,
     },
     Prefix {
-        operator: String,
+        operator: Operator,
         right: Option<Box<Node>>,
     },
     Infix {
         left: Option<Box<Node>>,
-        operator: String,
+        operator: Operator,
         right: Option<Box<Node>>,
     },
     BooleanLiteral {
@@ -37,9 +46,22 @@ pub enum Node {
         function: Option<Box<Node>>,
         arguments: Vec<Node>,
     },
+    Match {
+        subject: Option<Box<Node>>,
+        /// `(pattern, value)` pairs, tried in order
+        arms: Vec<(Node, Node)>,
+        /// Value of the `_ => ...` arm, if any
+        default: Option<Box<Node>>,
+    },
 
     // Statement variants
     Let {
+        name: Option<Box<Node>>,
+        /// Declared type from `let x: int = ...`, if any
+        type_annotation: Option<String>,
+        value: Option<Box<Node>>,
+    },
+    Assign {
         name: Option<Box<Node>>,
         value: Option<Box<Node>>,
     },
@@ -54,7 +76,271 @@ pub enum Node {
     },
 }
 
+/// Prefix and infix operators, converted from their tokens at parse time.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Operator {
+    Plus,
+    Minus,
+    Asterisk,
+    Slash,
+    Percent,
+    Bang,
+    LessThan,
+    GreaterThan,
+    LessEq,
+    GreaterEq,
+    Eq,
+    NotEq,
+    And,
+    Or,
+}
+
+impl Operator {
+    /// The operator spelled by `token`, if it is one.
+    pub fn from_token(token: &Token) -> Option<Operator> {
+        match token {
+            Token::Plus => Some(Operator::Plus),
+            Token::Minus => Some(Operator::Minus),
+            Token::Asterisk => Some(Operator::Asterisk),
+            Token::Slash => Some(Operator::Slash),
+            Token::Percent => Some(Operator::Percent),
+            Token::Bang => Some(Operator::Bang),
+            Token::LessThan => Some(Operator::LessThan),
+            Token::GreaterThan => Some(Operator::GreaterThan),
+            Token::LessEq => Some(Operator::LessEq),
+            Token::GreaterEq => Some(Operator::GreaterEq),
+            Token::And => Some(Operator::And),
+            Token::Or => Some(Operator::Or),
+            Token::Eq => Some(Operator::Eq),
+            Token::NotEq => Some(Operator::NotEq),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Plus => "+",
+            Operator::Minus => "-",
+            Operator::Asterisk => "*",
+            Operator::Slash => "/",
+            Operator::Percent => "%",
+            Operator::Bang => "!",
+            Operator::LessThan => "<",
+            Operator::GreaterThan => ">",
+            Operator::LessEq => "<=",
+            Operator::GreaterEq => ">=",
+            Operator::And => "&&",
+            Operator::Or => "||",
+            Operator::Eq => "==",
+            Operator::NotEq => "!=",
+        }
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// Fieldless mirror of `Node`'s variants, for dispatching on a node's shape
+/// without destructuring it.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum NodeKind {
+    Program,
+    IntegerLiteral,
+    CharLiteral,
+    Identifier,
+    Prefix,
+    Infix,
+    BooleanLiteral,
+    If,
+    Function,
+    Call,
+    Match,
+    Let,
+    Assign,
+    Return,
+    ExprStmt,
+    Block,
+}
+
+impl NodeKind {
+    /// Assignment counts as an expression since it evaluates to the assigned value.
+    pub fn is_expression(&self) -> bool {
+        matches!(
+            self,
+            NodeKind::IntegerLiteral
+                | NodeKind::CharLiteral
+                | NodeKind::Identifier
+                | NodeKind::Prefix
+                | NodeKind::Infix
+                | NodeKind::BooleanLiteral
+                | NodeKind::If
+                | NodeKind::Function
+                | NodeKind::Call
+                | NodeKind::Match
+                | NodeKind::Assign
+        )
+    }
+
+    /// `Program` is neither a statement nor an expression.
+    pub fn is_statement(&self) -> bool {
+        matches!(
+            self,
+            NodeKind::Let | NodeKind::Return | NodeKind::ExprStmt | NodeKind::Block
+        )
+    }
+}
+
 impl Node {
+    pub fn kind(&self) -> NodeKind {
+        match self {
+            Node::Program { .. } => NodeKind::Program,
+            Node::IntegerLiteral { .. } => NodeKind::IntegerLiteral,
+            Node::CharLiteral { .. } => NodeKind::CharLiteral,
+            Node::Identifier { .. } => NodeKind::Identifier,
+            Node::Prefix { .. } => NodeKind::Prefix,
+            Node::Infix { .. } => NodeKind::Infix,
+            Node::BooleanLiteral { .. } => NodeKind::BooleanLiteral,
+            Node::If { .. } => NodeKind::If,
+            Node::Function { .. } => NodeKind::Function,
+            Node::Call { .. } => NodeKind::Call,
+            Node::Match { .. } => NodeKind::Match,
+            Node::Let { .. } => NodeKind::Let,
+            Node::Assign { .. } => NodeKind::Assign,
+            Node::Return { .. } => NodeKind::Return,
+            Node::ExprStmt { .. } => NodeKind::ExprStmt,
+            Node::Block { .. } => NodeKind::Block,
+        }
+    }
+
+    /// Whether this node produces a value and may appear where a value is expected.
+    pub fn is_expression(&self) -> bool {
+        self.kind().is_expression()
+    }
+
+    /// Whether this node may only appear as one of a program's or block's statements.
+    pub fn is_statement(&self) -> bool {
+        self.kind().is_statement()
+    }
+
+    /// The nodes directly beneath this one, in source order.
+    pub fn children(&self) -> Vec<&Node> {
+        match self {
+            Node::Program { statements } | Node::Block { statements } => {
+                statements.iter().collect()
+            }
+            Node::IntegerLiteral { .. }
+            | Node::CharLiteral { .. }
+            | Node::Identifier { .. }
+            | Node::BooleanLiteral { .. } => vec![],
+            Node::Prefix { right, .. } => right.as_deref().into_iter().collect(),
+            Node::Infix { left, right, .. } => left
+                .as_deref()
+                .into_iter()
+                .chain(right.as_deref())
+                .collect(),
+            Node::If {
+                condition,
+                consequence,
+                alternative,
+            } => condition
+                .as_deref()
+                .into_iter()
+                .chain(consequence.as_deref())
+                .chain(alternative.as_deref())
+                .collect(),
+            Node::Function { parameters, body } => {
+                parameters.iter().chain(body.as_deref()).collect()
+            }
+            Node::Call {
+                function,
+                arguments,
+            } => function.as_deref().into_iter().chain(arguments).collect(),
+            Node::Match {
+                subject,
+                arms,
+                default,
+            } => subject
+                .as_deref()
+                .into_iter()
+                .chain(arms.iter().flat_map(|(pattern, value)| [pattern, value]))
+                .chain(default.as_deref())
+                .collect(),
+            Node::Let { name, value, .. } | Node::Assign { name, value } => name
+                .as_deref()
+                .into_iter()
+                .chain(value.as_deref())
+                .collect(),
+            Node::Return { return_value } => return_value.as_deref().into_iter().collect(),
+            Node::ExprStmt { expression } => expression.as_deref().into_iter().collect(),
+        }
+    }
+
+    /// How many nodes this tree has, counting this one.
+    pub fn node_count(&self) -> usize {
+        // Walks with an explicit stack so long operator chains can't overflow it
+        let mut pending = vec![self];
+        let mut count = 0;
+        while let Some(node) = pending.pop() {
+            count += 1;
+            pending.extend(node.children());
+        }
+        count
+    }
+
+    /// The number of nodes on the longest path down from this one; a leaf has depth 1.
+    pub fn depth(&self) -> usize {
+        let mut pending = vec![(self, 1)];
+        let mut deepest = 0;
+        while let Some((node, depth)) = pending.pop() {
+            deepest = deepest.max(depth);
+            pending.extend(node.children().into_iter().map(|child| (child, depth + 1)));
+        }
+        deepest
+    }
+
+    /// Renders the tree one node per line, indenting each node two spaces below its
+    /// parent. Unlike [`Node::string`] this shows the structure rather than source text.
+    pub fn debug_tree(&self) -> String {
+        let mut out = String::new();
+        let mut pending = vec![(TreeEntry::Node(self), 0)];
+        while let Some((entry, level)) = pending.pop() {
+            out.push_str(&"  ".repeat(level));
+            out.push_str(&entry.label());
+            out.push('\n');
+            // Reversed so the first child is popped, and printed, first
+            pending.extend(
+                entry
+                    .children()
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, level + 1)),
+            );
+        }
+        out
+    }
+
+    fn tree_label(&self) -> String {
+        match self {
+            Node::IntegerLiteral { .. }
+            | Node::CharLiteral { .. }
+            | Node::Identifier { .. }
+            | Node::BooleanLiteral { .. } => {
+                format!("{:?} {}", self.kind(), self.token_literal())
+            }
+            Node::Prefix { operator, .. } | Node::Infix { operator, .. } => {
+                format!("{:?} {}", self.kind(), operator)
+            }
+            Node::Let {
+                type_annotation: Some(annotation),
+                ..
+            } => format!("Let: {}", annotation),
+            _ => format!("{:?}", self.kind()),
+        }
+    }
+
     pub fn string(&self) -> String {
         match self {
             Node::Program { statements } => statements
@@ -63,6 +349,7 @@ impl Node {
                 .collect::<Vec<String>>()
                 .join(""),
             Node::IntegerLiteral { value } => value.to_string(),
+            Node::CharLiteral { value } => char_literal(*value),
             Node::Identifier { name } => name.clone(),
             Node::Prefix { operator, right } => {
                 format!(
@@ -121,9 +408,41 @@ impl Node {
                         .join(", ")
                 )
             }
-            Node::Let { name, value } => {
+            Node::Match {
+                subject,
+                arms,
+                default,
+            } => {
+                let arms = arms
+                    .iter()
+                    .map(|(pattern, value)| format!("{} => {}", pattern.string(), value.string()))
+                    .chain(default.iter().map(|d| format!("_ => {}", d.string())))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                let subject = subject.as_ref().map_or("".to_string(), |s| s.string());
+                if arms.is_empty() {
+                    format!("match {} {{}}", subject)
+                } else {
+                    format!("match {} {{ {} }}", subject, arms)
+                }
+            }
+            Node::Let {
+                name,
+                type_annotation,
+                value,
+            } => {
+                format!(
+                    "let {}{} = {}",
+                    name.as_ref().map_or("".to_string(), |n| n.string()),
+                    type_annotation
+                        .as_ref()
+                        .map_or("".to_string(), |t| format!(": {}", t)),
+                    value.as_ref().map_or("".to_string(), |v| v.string())
+                )
+            }
+            Node::Assign { name, value } => {
                 format!(
-                    "let {} = {}",
+                    "({} = {})",
                     name.as_ref().map_or("".to_string(), |n| n.string()),
                     value.as_ref().map_or("".to_string(), |v| v.string())
                 )
@@ -148,6 +467,7 @@ impl Node {
     pub fn token_literal(&self) -> String {
         match self {
             Node::IntegerLiteral { value } => value.to_string(),
+            Node::CharLiteral { value } => char_literal(*value),
             Node::Identifier { name } => name.clone(),
             Node::BooleanLiteral { value } => value.to_string(),
             _ => "".to_string(),
@@ -155,9 +475,55 @@ impl Node {
     }
 }
 
-#[derive(PartialEq, PartialOrd, Debug)]
+/// One line of [`Node::debug_tree`]: a node, or one of a match's arms, which
+/// aren't nodes themselves but group the nodes beneath them.
+enum TreeEntry<'a> {
+    Node(&'a Node),
+    Arm { pattern: &'a Node, value: &'a Node },
+    Default(&'a Node),
+}
+
+impl<'a> TreeEntry<'a> {
+    fn label(&self) -> String {
+        match self {
+            TreeEntry::Node(node) => node.tree_label(),
+            TreeEntry::Arm { .. } => "Arm".to_string(),
+            TreeEntry::Default(_) => "Default".to_string(),
+        }
+    }
+
+    fn children(&self) -> Vec<TreeEntry<'a>> {
+        match *self {
+            TreeEntry::Node(Node::Match {
+                subject,
+                arms,
+                default,
+            }) => subject
+                .as_deref()
+                .map(TreeEntry::Node)
+                .into_iter()
+                .chain(
+                    arms.iter()
+                        .map(|(pattern, value)| TreeEntry::Arm { pattern, value }),
+                )
+                .chain(default.as_deref().map(TreeEntry::Default))
+                .collect(),
+            TreeEntry::Node(node) => node.children().into_iter().map(TreeEntry::Node).collect(),
+            TreeEntry::Arm { pattern, value } => {
+                vec![TreeEntry::Node(pattern), TreeEntry::Node(value)]
+            }
+            TreeEntry::Default(value) => vec![TreeEntry::Node(value)],
+        }
+    }
+}
+
+/// Binding power of operators, from loosest to tightest.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
 pub enum Precedence {
     Lowest = 0,
+    Assign,      // x = y
+    Or,          // ||
+    And,         // &&
     Equals,      // ==
     LessGreater, // > or <
     Sum,         // +
@@ -165,3 +531,207 @@ pub enum Precedence {
     Prefix,      // -X or !X
     Call,        // myFunction(X)
 }
+
+/// How tightly `token` binds when it appears between two expressions.
+///
+/// Tokens that can't continue an expression get `Precedence::Lowest`.
+pub fn precedence_of(token: &Token) -> Precedence {
+    match token {
+        Token::Lparen => Precedence::Call,
+        Token::Assign => Precedence::Assign,
+        Token::Or => Precedence::Or,
+        Token::And => Precedence::And,
+        Token::Eq | Token::NotEq => Precedence::Equals,
+        Token::LessThan | Token::GreaterThan | Token::LessEq | Token::GreaterEq => {
+            Precedence::LessGreater
+        }
+        Token::Plus | Token::Minus => Precedence::Sum,
+        Token::Asterisk | Token::Slash | Token::Percent => Precedence::Product,
+        _ => Precedence::Lowest,
+    }
+}
+
+#[rstest]
+#[case(Node::Program { statements: vec![] }, NodeKind::Program)]
+#[case(Node::IntegerLiteral { value: 5 }, NodeKind::IntegerLiteral)]
+#[case(Node::CharLiteral { value: 'a' }, NodeKind::CharLiteral)]
+#[case(Node::Identifier { name: "x".to_string() }, NodeKind::Identifier)]
+#[case(Node::BooleanLiteral { value: true }, NodeKind::BooleanLiteral)]
+#[case(
+    Node::Prefix {
+        operator: Operator::Minus,
+        right: Some(Box::new(Node::IntegerLiteral { value: 1 })),
+    },
+    NodeKind::Prefix
+)]
+#[case(
+    Node::Infix {
+        left: Some(Box::new(Node::IntegerLiteral { value: 1 })),
+        operator: Operator::Plus,
+        right: Some(Box::new(Node::IntegerLiteral { value: 2 })),
+    },
+    NodeKind::Infix
+)]
+#[case(Node::Function { parameters: vec![], body: None }, NodeKind::Function)]
+#[case(Node::Call { function: None, arguments: vec![] }, NodeKind::Call)]
+#[case(
+    Node::Match { subject: None, arms: vec![], default: None },
+    NodeKind::Match
+)]
+#[case(
+    Node::Let { name: None, type_annotation: None, value: None },
+    NodeKind::Let
+)]
+#[case(Node::Assign { name: None, value: None }, NodeKind::Assign)]
+#[case(Node::Return { return_value: None }, NodeKind::Return)]
+#[case(Node::ExprStmt { expression: None }, NodeKind::ExprStmt)]
+#[case(Node::Block { statements: vec![] }, NodeKind::Block)]
+fn test_node_kind(#[case] node: Node, #[case] expected: NodeKind) {
+    assert_eq!(node.kind(), expected);
+}
+
+#[rstest]
+#[case(Token::Plus, Operator::Plus, "+")]
+#[case(Token::Minus, Operator::Minus, "-")]
+#[case(Token::Asterisk, Operator::Asterisk, "*")]
+#[case(Token::Slash, Operator::Slash, "/")]
+#[case(Token::Percent, Operator::Percent, "%")]
+#[case(Token::Bang, Operator::Bang, "!")]
+#[case(Token::LessThan, Operator::LessThan, "<")]
+#[case(Token::GreaterThan, Operator::GreaterThan, ">")]
+#[case(Token::LessEq, Operator::LessEq, "<=")]
+#[case(Token::GreaterEq, Operator::GreaterEq, ">=")]
+#[case(Token::And, Operator::And, "&&")]
+#[case(Token::Or, Operator::Or, "||")]
+#[case(Token::Eq, Operator::Eq, "==")]
+#[case(Token::NotEq, Operator::NotEq, "!=")]
+fn test_operator_from_token(
+    #[case] token: Token,
+    #[case] expected: Operator,
+    #[case] symbol: &str,
+) {
+    assert_eq!(Operator::from_token(&token), Some(expected));
+    assert_eq!(expected.to_string(), symbol);
+    assert_eq!(token.to_literal(), symbol);
+}
+
+#[rstest]
+#[case(Token::Assign)]
+#[case(Token::Lparen)]
+#[case(Token::Ident("x".to_string()))]
+fn test_non_operator_tokens(#[case] token: Token) {
+    assert_eq!(Operator::from_token(&token), None);
+}
+
+#[rstest]
+#[case(Token::Lparen, Precedence::Call)]
+#[case(Token::Assign, Precedence::Assign)]
+#[case(Token::Eq, Precedence::Equals)]
+#[case(Token::NotEq, Precedence::Equals)]
+#[case(Token::LessThan, Precedence::LessGreater)]
+#[case(Token::GreaterThan, Precedence::LessGreater)]
+#[case(Token::LessEq, Precedence::LessGreater)]
+#[case(Token::GreaterEq, Precedence::LessGreater)]
+#[case(Token::And, Precedence::And)]
+#[case(Token::Or, Precedence::Or)]
+#[case(Token::Plus, Precedence::Sum)]
+#[case(Token::Minus, Precedence::Sum)]
+#[case(Token::Asterisk, Precedence::Product)]
+#[case(Token::Slash, Precedence::Product)]
+#[case(Token::Percent, Precedence::Product)]
+#[case(Token::Semicolon, Precedence::Lowest)]
+#[case(Token::Ident("x".to_string()), Precedence::Lowest)]
+fn test_precedence_of(#[case] token: Token, #[case] expected: Precedence) {
+    assert_eq!(precedence_of(&token), expected);
+}
+
+#[rstest]
+#[case(NodeKind::Program, false, false)]
+#[case(NodeKind::IntegerLiteral, true, false)]
+#[case(NodeKind::CharLiteral, true, false)]
+#[case(NodeKind::Identifier, true, false)]
+#[case(NodeKind::Prefix, true, false)]
+#[case(NodeKind::Infix, true, false)]
+#[case(NodeKind::BooleanLiteral, true, false)]
+#[case(NodeKind::If, true, false)]
+#[case(NodeKind::Function, true, false)]
+#[case(NodeKind::Call, true, false)]
+#[case(NodeKind::Match, true, false)]
+#[case(NodeKind::Assign, true, false)]
+#[case(NodeKind::Let, false, true)]
+#[case(NodeKind::Return, false, true)]
+#[case(NodeKind::ExprStmt, false, true)]
+#[case(NodeKind::Block, false, true)]
+fn test_expression_statement_classification(
+    #[case] kind: NodeKind,
+    #[case] is_expression: bool,
+    #[case] is_statement: bool,
+) {
+    assert_eq!(kind.is_expression(), is_expression);
+    assert_eq!(kind.is_statement(), is_statement);
+}
+
+#[rstest]
+fn test_node_classification_follows_kind() {
+    let if_value = Node::If {
+        condition: Some(Box::new(Node::BooleanLiteral { value: true })),
+        consequence: Some(Box::new(Node::Block { statements: vec![] })),
+        alternative: None,
+    };
+    let bare_let = Node::Let {
+        name: Some(Box::new(Node::Identifier {
+            name: "x".to_string(),
+        })),
+        type_annotation: None,
+        value: Some(Box::new(Node::IntegerLiteral { value: 1 })),
+    };
+
+    assert!(if_value.is_expression() && !if_value.is_statement());
+    assert!(bare_let.is_statement() && !bare_let.is_expression());
+}
+
+#[rstest]
+#[case("", 1, 1)]
+#[case("5;", 3, 3)]
+#[case("let x = 1 + 2;", 6, 4)]
+#[case("let f = fn(a) { a * 2 }; f(3);", 14, 7)]
+#[case("if (x) { 1 } else { }", 8, 6)]
+#[case("match x { 1 => 2, _ => 3 }", 7, 4)]
+fn test_node_count_and_depth(#[case] input: &str, #[case] node_count: usize, #[case] depth: usize) {
+    let mut lexer = crate::lexer::Lexer::new(input);
+    let program = crate::parser::Parser::new(&mut lexer).parse_program();
+
+    assert_eq!(program.node_count(), node_count, "input: {}", input);
+    assert_eq!(program.depth(), depth, "input: {}", input);
+}
+
+#[rstest]
+fn test_metrics_of_long_chain() {
+    let input = vec!["1"; 10_000].join(" + ");
+    let mut lexer = crate::lexer::Lexer::new(&input);
+    let program = crate::parser::Parser::new(&mut lexer).parse_program();
+
+    assert_eq!(program.node_count(), 2 + 10_000 + 9_999);
+    assert_eq!(program.depth(), 2 + 10_000);
+}
+
+#[rstest]
+#[case(
+    "let x = 1 + 2;",
+    "Program\n  Let\n    Identifier x\n    Infix +\n      IntegerLiteral 1\n      IntegerLiteral 2\n"
+)]
+#[case(
+    "let ok: bool = !f('a');",
+    "Program\n  Let: bool\n    Identifier ok\n    Prefix !\n      Call\n        Identifier f\n        CharLiteral 'a'\n"
+)]
+#[case(
+    "match x { 1 => 'a', _ => 'b' }",
+    "Program\n  ExprStmt\n    Match\n      Identifier x\n      Arm\n        IntegerLiteral 1\n        CharLiteral 'a'\n      Default\n        CharLiteral 'b'\n"
+)]
+#[case("", "Program\n")]
+fn test_debug_tree(#[case] input: &str, #[case] expected: &str) {
+    let mut lexer = crate::lexer::Lexer::new(input);
+    let program = crate::parser::Parser::new(&mut lexer).parse_program();
+
+    assert_eq!(program.debug_tree(), expected);
+}
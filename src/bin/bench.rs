@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use roadrunner::corpus::{self, PROGRAMS};
+use roadrunner::timing::{mean, median, time_phase};
+
+const DEFAULT_ITERATIONS: usize = 20;
+
+fn main() -> Result<(), anyhow::Error> {
+    let iterations = match std::env::args().nth(1) {
+        Some(arg) => arg
+            .parse::<usize>()
+            .map_err(|_| anyhow!("usage: bench [iterations]"))?,
+        None => DEFAULT_ITERATIONS,
+    };
+
+    for program in PROGRAMS {
+        let mut samples: Vec<Duration> = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            let (result, elapsed) = time_phase(|| corpus::run(program));
+            if result != program.expected {
+                return Err(anyhow!(
+                    "{}: expected {}, got {}",
+                    program.name,
+                    program.expected,
+                    result
+                ));
+            }
+            samples.push(elapsed);
+        }
+
+        println!(
+            "{:<10} mean: {:>12?} median: {:>12?} ({} runs)",
+            program.name,
+            mean(&samples),
+            median(&samples),
+            iterations
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,8 @@
+fn main() -> Result<(), anyhow::Error> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match roadrunner::cli::dispatch(&args)? {
+        0 => Ok(()),
+        code => std::process::exit(code),
+    }
+}
@@ -0,0 +1,13 @@
+use anyhow::anyhow;
+use roadrunner::cli::{parse_run_args, run_script};
+
+fn main() -> Result<(), anyhow::Error> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (path, options) =
+        parse_run_args(&args).ok_or_else(|| anyhow!("usage: run [--time] [--check] <file>"))?;
+
+    match run_script(path, &options)? {
+        0 => Ok(()),
+        code => std::process::exit(code),
+    }
+}
@@ -0,0 +1,332 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::{
+    evaluator::{Evaluator, code_point_to_char},
+    object::{Builtin, Object},
+};
+
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "is_nil",
+        func: is_nil,
+        accepts_errors: false,
+        arity: Some(1),
+    },
+    Builtin {
+        name: "is_error",
+        func: is_error,
+        accepts_errors: true,
+        arity: Some(1),
+    },
+    Builtin {
+        name: "is_int",
+        func: is_int,
+        accepts_errors: false,
+        arity: Some(1),
+    },
+    Builtin {
+        name: "is_bool",
+        func: is_bool,
+        accepts_errors: false,
+        arity: Some(1),
+    },
+    Builtin {
+        name: "is_fn",
+        func: is_fn,
+        accepts_errors: false,
+        arity: Some(1),
+    },
+    Builtin {
+        name: "int",
+        func: int,
+        accepts_errors: false,
+        arity: Some(1),
+    },
+    Builtin {
+        name: "char",
+        func: char,
+        accepts_errors: false,
+        arity: Some(1),
+    },
+    Builtin {
+        name: "clamp",
+        func: clamp,
+        accepts_errors: false,
+        arity: Some(3),
+    },
+    Builtin {
+        name: "gcd",
+        func: gcd,
+        accepts_errors: false,
+        arity: Some(2),
+    },
+    Builtin {
+        name: "lcm",
+        func: lcm,
+        accepts_errors: false,
+        arity: Some(2),
+    },
+    Builtin {
+        name: "pow",
+        func: pow,
+        accepts_errors: false,
+        arity: Some(2),
+    },
+    Builtin {
+        name: "sleep",
+        func: sleep,
+        accepts_errors: false,
+        arity: Some(1),
+    },
+    Builtin {
+        name: "time_ns",
+        func: time_ns,
+        accepts_errors: false,
+        arity: Some(0),
+    },
+    Builtin {
+        name: "puts",
+        func: puts,
+        accepts_errors: false,
+        arity: None,
+    },
+    Builtin {
+        name: "print",
+        func: print,
+        accepts_errors: false,
+        arity: None,
+    },
+    Builtin {
+        name: "dbg",
+        func: dbg,
+        accepts_errors: true,
+        arity: Some(1),
+    },
+];
+
+/// Returns the builtin function bound to `name`, if there is one.
+pub fn lookup(name: &str) -> Option<Object> {
+    BUILTINS
+        .iter()
+        .find(|builtin| builtin.name == name)
+        .map(|builtin| Object::Builtin(*builtin))
+}
+
+fn wrong_argument_count(got: usize, want: usize) -> Object {
+    Object::Error(format!(
+        "wrong number of arguments. got={}, want={}",
+        got, want
+    ))
+}
+
+fn predicate(args: Vec<Object>, test: impl Fn(&Object) -> bool) -> Object {
+    match args.as_slice() {
+        [arg] => Object::Boolean(test(arg)),
+        _ => wrong_argument_count(args.len(), 1),
+    }
+}
+
+fn is_nil(args: Vec<Object>, _: &Evaluator) -> Object {
+    predicate(args, |obj| matches!(obj, Object::Null))
+}
+
+fn is_error(args: Vec<Object>, _: &Evaluator) -> Object {
+    predicate(args, Object::is_error)
+}
+
+fn is_int(args: Vec<Object>, _: &Evaluator) -> Object {
+    predicate(args, |obj| matches!(obj, Object::Integer(_)))
+}
+
+fn is_bool(args: Vec<Object>, _: &Evaluator) -> Object {
+    predicate(args, |obj| matches!(obj, Object::Boolean(_)))
+}
+
+fn is_fn(args: Vec<Object>, _: &Evaluator) -> Object {
+    predicate(args, |obj| {
+        matches!(obj, Object::Function { .. } | Object::Builtin(_))
+    })
+}
+
+fn unsupported_argument(name: &str, arg: &Object) -> Object {
+    Object::Error(format!(
+        "argument to `{}` not supported, got {}",
+        name,
+        arg.type_name()
+    ))
+}
+
+fn int(args: Vec<Object>, _: &Evaluator) -> Object {
+    match args.as_slice() {
+        [Object::Integer(value)] => Object::Integer(*value),
+        [Object::Char(c)] => Object::Integer(i64::from(u32::from(*c))),
+        [other] => unsupported_argument("int", other),
+        _ => wrong_argument_count(args.len(), 1),
+    }
+}
+
+fn char(args: Vec<Object>, _: &Evaluator) -> Object {
+    match args.as_slice() {
+        [Object::Char(c)] => Object::Char(*c),
+        [Object::Integer(code_point)] => code_point_to_char(*code_point),
+        [other] => unsupported_argument("char", other),
+        _ => wrong_argument_count(args.len(), 1),
+    }
+}
+
+/// `clamp(x, lo, hi)` bounds the integer `x` to the inclusive range `[lo, hi]`.
+fn clamp(args: Vec<Object>, _: &Evaluator) -> Object {
+    match args.as_slice() {
+        [Object::Integer(_), Object::Integer(lo), Object::Integer(hi)] if lo > hi => {
+            Object::Error(format!("invalid range for `clamp`: {} > {}", lo, hi))
+        }
+        [Object::Integer(x), Object::Integer(lo), Object::Integer(hi)] => {
+            Object::Integer((*x).clamp(*lo, *hi))
+        }
+        [x, lo, hi] => {
+            let other = [x, lo, hi]
+                .into_iter()
+                .find(|arg| !matches!(arg, Object::Integer(_)))
+                .unwrap_or(x);
+            unsupported_argument("clamp", other)
+        }
+        _ => wrong_argument_count(args.len(), 3),
+    }
+}
+
+/// The first argument that isn't an integer, for reporting unsupported calls.
+fn first_non_integer(args: &[Object]) -> &Object {
+    args.iter()
+        .find(|arg| !matches!(arg, Object::Integer(_)))
+        .unwrap_or(&args[0])
+}
+
+fn unsigned_gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// `gcd(a, b)` is the non-negative greatest common divisor; `gcd(0, 0)` is `0`.
+fn gcd(args: Vec<Object>, _: &Evaluator) -> Object {
+    match args.as_slice() {
+        [Object::Integer(a), Object::Integer(b)] => {
+            let divisor = unsigned_gcd(a.unsigned_abs(), b.unsigned_abs());
+            i64::try_from(divisor)
+                .map(Object::Integer)
+                .unwrap_or_else(|_| Object::Error(format!("integer overflow: gcd({}, {})", a, b)))
+        }
+        [_, _] => unsupported_argument("gcd", first_non_integer(&args)),
+        _ => wrong_argument_count(args.len(), 2),
+    }
+}
+
+/// `lcm(a, b)` is the non-negative least common multiple; it is `0` if either is `0`.
+fn lcm(args: Vec<Object>, _: &Evaluator) -> Object {
+    match args.as_slice() {
+        [Object::Integer(0), Object::Integer(_)] | [Object::Integer(_), Object::Integer(0)] => {
+            Object::Integer(0)
+        }
+        [Object::Integer(a), Object::Integer(b)] => {
+            let (a_abs, b_abs) = (a.unsigned_abs(), b.unsigned_abs());
+            (a_abs / unsigned_gcd(a_abs, b_abs))
+                .checked_mul(b_abs)
+                .and_then(|multiple| i64::try_from(multiple).ok())
+                .map(Object::Integer)
+                .unwrap_or_else(|| Object::Error(format!("integer overflow: lcm({}, {})", a, b)))
+        }
+        [_, _] => unsupported_argument("lcm", first_non_integer(&args)),
+        _ => wrong_argument_count(args.len(), 2),
+    }
+}
+
+/// `pow(base, exp)` raises an integer to a non-negative integer power.
+fn pow(args: Vec<Object>, _: &Evaluator) -> Object {
+    match args.as_slice() {
+        [Object::Integer(_), Object::Integer(exp)] if *exp < 0 => Object::Error(format!(
+            "exponent to `pow` must not be negative, got {}",
+            exp
+        )),
+        // These bases never grow, so any exponent works, even one past u32
+        [Object::Integer(base @ (0 | 1)), Object::Integer(exp)] if *exp > 0 => {
+            Object::Integer(*base)
+        }
+        [Object::Integer(-1), Object::Integer(exp)] => {
+            Object::Integer(if exp % 2 == 0 { 1 } else { -1 })
+        }
+        [Object::Integer(base), Object::Integer(exp)] => u32::try_from(*exp)
+            .ok()
+            .and_then(|exp| base.checked_pow(exp))
+            .map(Object::Integer)
+            .unwrap_or_else(|| Object::Error(format!("integer overflow: pow({}, {})", base, exp))),
+        [_, _] => unsupported_argument("pow", first_non_integer(&args)),
+        _ => wrong_argument_count(args.len(), 2),
+    }
+}
+
+/// Impure: pauses for `ms` milliseconds, or returns at once in sandbox mode.
+fn sleep(args: Vec<Object>, ev: &Evaluator) -> Object {
+    match args.as_slice() {
+        [Object::Integer(ms)] if *ms < 0 => Object::Error(format!(
+            "argument to `sleep` must not be negative, got {}",
+            ms
+        )),
+        [Object::Integer(_)] if ev.is_sandboxed() => Object::Null,
+        [Object::Integer(ms)] => {
+            std::thread::sleep(Duration::from_millis(ms.unsigned_abs()));
+            Object::Null
+        }
+        [other] => unsupported_argument("sleep", other),
+        _ => wrong_argument_count(args.len(), 1),
+    }
+}
+
+/// Impure: nanoseconds on a monotonic clock, measured from the first call in this process.
+fn time_ns(args: Vec<Object>, ev: &Evaluator) -> Object {
+    static START: OnceLock<Instant> = OnceLock::new();
+
+    if !args.is_empty() {
+        return wrong_argument_count(args.len(), 0);
+    }
+    if ev.is_sandboxed() {
+        return Object::Error("`time_ns` is not available in sandbox mode".to_string());
+    }
+    let elapsed = START.get_or_init(Instant::now).elapsed();
+    Object::Integer(i64::try_from(elapsed.as_nanos()).unwrap_or(i64::MAX))
+}
+
+/// How `puts` and `print` write a value: chars as the bare character, so output
+/// can be built up piece by piece, and anything else as it inspects.
+fn output_text(obj: &Object) -> String {
+    match obj {
+        Object::Char(c) => c.to_string(),
+        other => other.inspect(),
+    }
+}
+
+/// Writes each argument on its own line.
+fn puts(args: Vec<Object>, ev: &Evaluator) -> Object {
+    args.iter()
+        .for_each(|arg| ev.write_output(&format!("{}\n", output_text(arg))));
+    Object::Null
+}
+
+/// Writes the arguments back to back, without separators or a trailing newline.
+fn print(args: Vec<Object>, ev: &Evaluator) -> Object {
+    args.iter()
+        .for_each(|arg| ev.write_output(&output_text(arg)));
+    Object::Null
+}
+
+/// Writes its argument to the evaluator's output and passes it through unchanged.
+fn dbg(args: Vec<Object>, ev: &Evaluator) -> Object {
+    match <[Object; 1]>::try_from(args) {
+        Ok([arg]) => {
+            ev.write_output(&format!("dbg: {}\n", arg.inspect()));
+            arg
+        }
+        Err(args) => wrong_argument_count(args.len(), 1),
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::anyhow;
+use rstest::rstest;
+
+use crate::evaluator::Evaluator;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::repl::{ReplOptions, run_repl};
+use crate::timing::{PhaseTimings, time_phase};
+
+const USAGE: &str = "usage: roadrunner <repl | run [--time] <file> | check <file>>";
+
+/// How `run_script` should treat a script.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RunOptions {
+    /// Print per-phase timings to stderr after evaluating.
+    pub show_timings: bool,
+    /// Only lex and parse, reporting syntax errors without evaluating.
+    pub check_only: bool,
+}
+
+/// Splits script arguments into the script path (the first argument that isn't
+/// a `--` flag) and the options set by `--time` and `--check`.
+///
+/// Returns `None` when no path is given.
+pub fn parse_run_args(args: &[String]) -> Option<(&str, RunOptions)> {
+    let path = args.iter().find(|arg| !arg.starts_with("--"))?;
+    let options = RunOptions {
+        show_timings: args.iter().any(|arg| arg == "--time"),
+        check_only: args.iter().any(|arg| arg == "--check"),
+    };
+    Some((path, options))
+}
+
+/// Dispatches a `roadrunner` subcommand, returning the process exit code.
+pub fn dispatch(args: &[String]) -> Result<i32, anyhow::Error> {
+    match args.split_first() {
+        Some((command, _)) if command == "repl" => {
+            repl()?;
+            Ok(0)
+        }
+        Some((command, rest)) if command == "run" => {
+            let (path, options) = parse_run_args(rest).ok_or_else(|| anyhow!(USAGE))?;
+            run_script(path, &options)
+        }
+        Some((command, rest)) if command == "check" => {
+            let (path, options) = parse_run_args(rest).ok_or_else(|| anyhow!(USAGE))?;
+            run_script(
+                path,
+                &RunOptions {
+                    check_only: true,
+                    ..options
+                },
+            )
+        }
+        _ => Err(anyhow!(USAGE)),
+    }
+}
+
+/// Parses and (unless only checking) evaluates the script at `path`, printing its result.
+///
+/// Returns exit code 1 when the script has syntax errors or evaluates to an error.
+pub fn run_script(path: &str, options: &RunOptions) -> Result<i32, anyhow::Error> {
+    let source = std::fs::read_to_string(path)?;
+    let mut timings = PhaseTimings::default();
+
+    let mut lexer = Lexer::new(&source);
+    let (parsed, parse_time) = time_phase(|| Parser::new(&mut lexer).parse());
+    timings.parse = parse_time;
+
+    let program = match parsed {
+        Ok(program) => program,
+        Err(errors) => {
+            for err in errors.iter() {
+                eprintln!(
+                    "{}:{}: {} (at {})",
+                    path, err.position, err.message, err.token
+                );
+            }
+            return Ok(1);
+        }
+    };
+
+    if options.check_only {
+        return Ok(0);
+    }
+
+    let evaluator = Evaluator::new();
+    let (evaluated, eval_time) = time_phase(|| evaluator.eval(program, evaluator.environment()));
+    timings.eval = eval_time;
+
+    println!("{}", evaluated);
+
+    if options.show_timings {
+        eprintln!("{}", timings.report());
+    }
+
+    Ok(if evaluated.is_error() { 1 } else { 0 })
+}
+
+/// Starts the interactive REPL with file logging under `./logs`.
+pub fn repl() -> Result<(), anyhow::Error> {
+    tracing::debug!("Debug Test: Application started");
+
+    if !tracing::dispatcher::has_been_set() {
+        let file_appender = tracing_appender::rolling::daily("./logs", "trace.log");
+        let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+        std::mem::forget(guard); // Prevent early drop of the logging guard
+        let _ = std::fs::write("./logs/debug_test.log", "Testing log output: Successful\n");
+        tracing_subscriber::fmt()
+            .with_writer(non_blocking)
+            .with_ansi(false) // Disable ANSI escape codes for clean logs
+            .with_max_level(tracing::Level::DEBUG)
+            .try_init()
+            .expect("Tracing initialization failed");
+        tracing::info!("Tracing information initialized");
+    }
+
+    run_repl(&ReplOptions::default())
+}
+
+#[rstest]
+#[case(&["script.rr"], Some(("script.rr", false, false)))]
+#[case(&["--time", "script.rr"], Some(("script.rr", true, false)))]
+#[case(&["script.rr", "--check", "--time"], Some(("script.rr", true, true)))]
+#[case(&["--check"], None)]
+fn test_parse_run_args(#[case] args: &[&str], #[case] expected: Option<(&str, bool, bool)>) {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+
+    let parsed = parse_run_args(&args)
+        .map(|(path, options)| (path, options.show_timings, options.check_only));
+    assert_eq!(parsed, expected);
+}
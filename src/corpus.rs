@@ -0,0 +1,71 @@
+use rstest::rstest;
+
+use crate::evaluator::Evaluator;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+
+/// A representative Roadrunner program and the value it should evaluate to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CorpusProgram {
+    pub name: &'static str,
+    pub source: &'static str,
+    /// `inspect()` output of the program's result.
+    pub expected: &'static str,
+}
+
+/// Programs exercising recursion, closures, the prelude and char arithmetic.
+///
+/// Shared by the `bench` binary and the tests below so a benchmark never times
+/// a program that has quietly started returning an error.
+pub const PROGRAMS: &[CorpusProgram] = &[
+    CorpusProgram {
+        name: "fib",
+        source: "
+            let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } };
+            fib(15);
+        ",
+        expected: "610",
+    },
+    CorpusProgram {
+        name: "closures",
+        source: "
+            let adder = fn(x) { fn(y) { x + y } };
+            let inc = adder(1);
+            let twice = compose(inc, inc);
+            let repeat = fn(i, acc) { if (i == 0) { acc } else { repeat(i - 1, twice(acc)) } };
+            repeat(100, 0);
+        ",
+        expected: "200",
+    },
+    CorpusProgram {
+        name: "prelude",
+        source: "
+            let sum = fn(n, acc) { if (n == 0) { acc } else { sum(n - 1, acc + max(n, 0 - n)) } };
+            sum(100, 0);
+        ",
+        expected: "5050",
+    },
+    CorpusProgram {
+        name: "chars",
+        source: "
+            let shift = fn(c, n) { if (n == 0) { c } else { shift(c + 1, n - 1) } };
+            shift('a', 25);
+        ",
+        expected: "'z'",
+    },
+];
+
+/// Parses and evaluates `program` with the default evaluator and prelude.
+pub fn run(program: &CorpusProgram) -> String {
+    let mut lexer = Lexer::new(program.source);
+    let parsed = Parser::new(&mut lexer).parse_program();
+    let evaluator = Evaluator::new();
+    evaluator.eval(parsed, evaluator.environment()).inspect()
+}
+
+#[rstest]
+fn test_corpus_programs() {
+    for program in PROGRAMS {
+        assert_eq!(run(program), program.expected, "program: {}", program.name);
+    }
+}
@@ -1,5 +1,7 @@
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
+use rstest::rstest;
+
 use crate::object::Object;
 
 pub type Env = Rc<RefCell<Environment>>;
@@ -8,6 +10,7 @@ pub type Env = Rc<RefCell<Environment>>;
 pub struct Environment {
     store: HashMap<String, Object>,
     outer: Option<Env>,
+    frozen: bool,
 }
 
 impl Environment {
@@ -15,6 +18,7 @@ impl Environment {
         Rc::new(RefCell::new(Environment {
             store: HashMap::new(),
             outer: None,
+            frozen: false,
         }))
     }
 
@@ -22,6 +26,17 @@ impl Environment {
         Rc::new(RefCell::new(Environment {
             store: HashMap::new(),
             outer: Some(outer),
+            frozen: false,
+        }))
+    }
+
+    /// Creates an enclosed scope with room for `capacity` bindings, such as a
+    /// function call's parameters, without rehashing.
+    pub fn with_capacity(capacity: usize, outer: Env) -> Env {
+        Rc::new(RefCell::new(Environment {
+            store: HashMap::with_capacity(capacity),
+            outer: Some(outer),
+            frozen: false,
         }))
     }
 
@@ -32,7 +47,175 @@ impl Environment {
         }
     }
 
-    pub fn set(&mut self, name: &str, val: Object) {
+    /// Looks up `name` in this scope only, without consulting any outer scope.
+    pub fn get_own(&self, name: &str) -> Option<Object> {
+        self.store.get(name).cloned()
+    }
+
+    /// Binds `name` in this scope, failing with an error object if the scope is frozen.
+    pub fn set(&mut self, name: &str, val: Object) -> Result<(), Object> {
+        self.ensure_mutable()?;
         self.store.insert(name.to_string(), val);
+        Ok(())
+    }
+
+    /// Makes this scope read-only: later `set`, `assign` and `merge` calls into it fail.
+    ///
+    /// Scopes enclosed by it stay mutable, so function calls can still bind parameters.
+    pub fn freeze(&mut self) {
+        self.frozen = true;
     }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    fn ensure_mutable(&self) -> Result<(), Object> {
+        if self.frozen {
+            Err(Object::Error("environment is frozen".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Copies `other`'s own bindings into this scope; `other`'s outer scopes are ignored.
+    ///
+    /// On a name conflict the last merge wins, so `other`'s value replaces any existing one.
+    pub fn merge(&mut self, other: &Environment) -> Result<(), Object> {
+        self.ensure_mutable()?;
+        self.store.extend(
+            other
+                .store
+                .iter()
+                .map(|(name, val)| (name.clone(), val.clone())),
+        );
+        Ok(())
+    }
+
+    /// Rebinds `name` in the nearest scope that already defines it.
+    ///
+    /// Fails when no enclosing scope has a binding for `name` or the defining scope is frozen.
+    pub fn assign(&mut self, name: &str, val: Object) -> Result<(), Object> {
+        if self.get_own(name).is_some() {
+            return self.set(name, val);
+        }
+        match &self.outer {
+            Some(outer) => outer.borrow_mut().assign(name, val),
+            None => Err(Object::Error(format!("identifier not found: {}", name))),
+        }
+    }
+}
+
+#[rstest]
+fn test_get_own_ignores_outer_scope() {
+    let outer = Environment::new();
+    outer.borrow_mut().set("x", Object::Integer(1)).unwrap();
+
+    let inner = Environment::new_enclosed(Rc::clone(&outer));
+
+    assert_eq!(inner.borrow().get("x"), Some(Object::Integer(1)));
+    assert_eq!(inner.borrow().get_own("x"), None);
+}
+
+#[rstest]
+fn test_get_own_sees_shadowed_local() {
+    let outer = Environment::new();
+    outer.borrow_mut().set("x", Object::Integer(1)).unwrap();
+
+    let inner = Environment::new_enclosed(Rc::clone(&outer));
+    inner.borrow_mut().set("x", Object::Integer(2)).unwrap();
+
+    assert_eq!(inner.borrow().get_own("x"), Some(Object::Integer(2)));
+    assert_eq!(outer.borrow().get_own("x"), Some(Object::Integer(1)));
+}
+
+#[rstest]
+fn test_assign_updates_defining_scope() {
+    let outer = Environment::new();
+    outer.borrow_mut().set("x", Object::Integer(1)).unwrap();
+
+    let inner = Environment::new_enclosed(Rc::clone(&outer));
+
+    assert_eq!(inner.borrow_mut().assign("x", Object::Integer(2)), Ok(()));
+    assert_eq!(inner.borrow().get_own("x"), None);
+    assert_eq!(outer.borrow().get_own("x"), Some(Object::Integer(2)));
+}
+
+#[rstest]
+fn test_assign_undefined_name_fails() {
+    let env = Environment::new();
+
+    assert_eq!(
+        env.borrow_mut().assign("missing", Object::Integer(1)),
+        Err(Object::Error("identifier not found: missing".to_string()))
+    );
+    assert_eq!(env.borrow().get("missing"), None);
+}
+
+#[rstest]
+fn test_with_capacity_encloses_outer() {
+    let outer = Environment::new();
+    outer.borrow_mut().set("x", Object::Integer(1)).unwrap();
+
+    let inner = Environment::with_capacity(2, Rc::clone(&outer));
+    inner.borrow_mut().set("y", Object::Integer(2)).unwrap();
+
+    assert!(inner.borrow().store.capacity() >= 2);
+    assert_eq!(inner.borrow().get("x"), Some(Object::Integer(1)));
+    assert_eq!(inner.borrow().get_own("y"), Some(Object::Integer(2)));
+}
+
+#[rstest]
+fn test_merge_combines_bindings_last_wins() {
+    let session = Environment::new();
+    session.borrow_mut().set("x", Object::Integer(1)).unwrap();
+    session.borrow_mut().set("y", Object::Integer(2)).unwrap();
+
+    let outer = Environment::new();
+    outer
+        .borrow_mut()
+        .set("hidden", Object::Integer(0))
+        .unwrap();
+    let loaded = Environment::new_enclosed(outer);
+    loaded.borrow_mut().set("y", Object::Integer(20)).unwrap();
+    loaded.borrow_mut().set("z", Object::Integer(30)).unwrap();
+
+    session.borrow_mut().merge(&loaded.borrow()).unwrap();
+
+    let session = session.borrow();
+    assert_eq!(session.get_own("x"), Some(Object::Integer(1)));
+    assert_eq!(session.get_own("y"), Some(Object::Integer(20)));
+    assert_eq!(session.get_own("z"), Some(Object::Integer(30)));
+    assert_eq!(session.get("hidden"), None);
+    assert_eq!(loaded.borrow().get_own("y"), Some(Object::Integer(20)));
+}
+
+#[rstest]
+fn test_frozen_environment_rejects_writes() {
+    let frozen_error = Err(Object::Error("environment is frozen".to_string()));
+    let env = Environment::new();
+    env.borrow_mut().set("x", Object::Integer(1)).unwrap();
+    env.borrow_mut().freeze();
+
+    assert!(env.borrow().is_frozen());
+    assert_eq!(env.borrow().get("x"), Some(Object::Integer(1)));
+    assert_eq!(env.borrow_mut().set("y", Object::Integer(2)), frozen_error);
+    assert_eq!(
+        env.borrow_mut().assign("x", Object::Integer(2)),
+        frozen_error
+    );
+    assert_eq!(
+        env.borrow_mut().merge(&Environment::new().borrow()),
+        frozen_error
+    );
+    assert_eq!(env.borrow().get("x"), Some(Object::Integer(1)));
+    assert_eq!(env.borrow().get("y"), None);
+
+    let child = Environment::new_enclosed(Rc::clone(&env));
+    assert_eq!(child.borrow_mut().set("y", Object::Integer(3)), Ok(()));
+    assert_eq!(child.borrow().get("y"), Some(Object::Integer(3)));
+    assert_eq!(
+        child.borrow_mut().assign("x", Object::Integer(4)),
+        frozen_error
+    );
 }
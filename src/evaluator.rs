@@ -1,49 +1,242 @@
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::io::Write;
 use std::rc::Rc;
 
 use rstest::rstest;
 use tracing::debug;
 
 use crate::{
-    ast::Node,
+    ast::{Node, Operator},
+    builtins,
     environment::{Env, Environment},
+    lexer::Lexer,
     object::Object,
+    parser::Parser,
+    token::char_literal,
 };
 
 const TRUE: Object = Object::Boolean(true);
 const FALSE: Object = Object::Boolean(false);
 const NULL: Object = Object::Null;
 
+/// Standard library written in Roadrunner itself, loaded into new base environments.
+pub const PRELUDE: &str = include_str!("prelude.rr");
+
+/// Destination for text written by builtins such as `puts` and `dbg`.
+pub type OutputSink = Rc<RefCell<dyn Write>>;
+
+#[derive(Clone)]
+struct Output(OutputSink);
+
+impl Debug for Output {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Output")
+    }
+}
+
+/// Evaluation options threaded through the evaluator's free functions.
+///
+/// The defaults are the language's standard (strict) semantics; the `with_*`
+/// builders opt into alternative behaviour.
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    bool_coercion: bool,
+    prelude: bool,
+    type_checks: bool,
+    wrapping_arithmetic: bool,
+    sandbox: bool,
+    strict_conditions: bool,
+    output: Output,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Evaluator {
+            bool_coercion: false,
+            prelude: true,
+            type_checks: false,
+            wrapping_arithmetic: false,
+            sandbox: false,
+            strict_conditions: false,
+            output: Output(Rc::new(RefCell::new(std::io::stdout()))),
+        }
+    }
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks `let x: type = ...` annotations against the value actually bound.
+    pub fn with_type_checks(mut self, enabled: bool) -> Self {
+        self.type_checks = enabled;
+        self
+    }
+
+    /// Makes integer `+ - *` wrap around on overflow instead of producing an error.
+    pub fn with_wrapping_arithmetic(mut self, enabled: bool) -> Self {
+        self.wrapping_arithmetic = enabled;
+        self
+    }
+
+    /// Requires `if` conditions to be booleans instead of treating any non-`false`, non-null value as true.
+    pub fn with_strict_conditions(mut self, enabled: bool) -> Self {
+        self.strict_conditions = enabled;
+        self
+    }
+
+    /// Disables impure builtins: `sleep` returns immediately and `time_ns` is an error.
+    pub fn with_sandbox(mut self, enabled: bool) -> Self {
+        self.sandbox = enabled;
+        self
+    }
+
+    pub(crate) fn is_sandboxed(&self) -> bool {
+        self.sandbox
+    }
+
+    /// Sends builtin output to `sink` instead of stdout.
+    pub fn with_output(mut self, sink: OutputSink) -> Self {
+        self.output = Output(sink);
+        self
+    }
+
+    /// Writes `text` to the output sink as-is, ignoring write failures.
+    pub(crate) fn write_output(&self, text: &str) {
+        write!(self.output.0.borrow_mut(), "{}", text).ok();
+    }
+
+    /// Loads the [`PRELUDE`] into environments created by [`Evaluator::environment`].
+    pub fn with_prelude(mut self) -> Self {
+        self.prelude = true;
+        self
+    }
+
+    /// Starts environments created by [`Evaluator::environment`] empty.
+    pub fn without_prelude(mut self) -> Self {
+        self.prelude = false;
+        self
+    }
+
+    /// Creates a fresh top-level environment, evaluating the prelude into it when enabled.
+    pub fn environment(&self) -> Env {
+        let env = Environment::new();
+        if self.prelude {
+            let mut lexer = Lexer::new(PRELUDE);
+            let program = Parser::new(&mut lexer).parse_program();
+            self.eval(program, Rc::clone(&env));
+        }
+        env
+    }
+
+    /// Lets booleans coerce to `1`/`0` when used in arithmetic (`true + 1 == 2`).
+    pub fn with_bool_coercion(mut self, enabled: bool) -> Self {
+        self.bool_coercion = enabled;
+        self
+    }
+
+    /// Evaluates an AST node in the given environment using these options
+    pub fn eval(&self, node: Node, env: Env) -> Object {
+        eval_node(node, env, self)
+    }
+
+    /// Evaluates `program` statement by statement, carrying on past statements that
+    /// fail instead of stopping at the first error.
+    ///
+    /// Returns the value of the last statement that succeeded (or of a top-level
+    /// `return`, which still ends the program) with every error message in order.
+    pub fn eval_collecting_errors(&self, program: Node, env: Env) -> (Object, Vec<String>) {
+        let statements = match program {
+            Node::Program { statements } => statements,
+            other => vec![other],
+        };
+
+        let mut errors = Vec::new();
+        let mut value = NULL;
+        for statement in statements {
+            match eval_node(statement, Rc::clone(&env), self) {
+                Object::Error(message) => errors.push(message),
+                Object::ReturnValue(returned) => return (*returned, errors),
+                result => value = result,
+            }
+        }
+        (value, errors)
+    }
+
+    /// Parses `input` as a single expression and evaluates it, without the program
+    /// wrapper. Syntax errors and anything other than exactly one expression are errors.
+    pub fn eval_expr(&self, input: &str, env: Env) -> Object {
+        let mut lexer = Lexer::new(input);
+        let statements = match Parser::new(&mut lexer).parse() {
+            Ok(Node::Program { statements }) => statements,
+            Ok(other) => vec![other],
+            Err(errors) => {
+                let messages: Vec<String> = errors.into_iter().map(|e| e.message).collect();
+                return Object::Error(format!("syntax error: {}", messages.join("; ")));
+            }
+        };
+
+        match <[Node; 1]>::try_from(statements) {
+            Ok([Node::ExprStmt {
+                expression: Some(expression),
+            }]) => eval_node(*expression, env, self),
+            Ok([statement]) => Object::Error(format!(
+                "expected an expression, got a {:?} statement",
+                statement.kind()
+            )),
+            Err(statements) => Object::Error(format!(
+                "expected a single expression, got {} statements",
+                statements.len()
+            )),
+        }
+    }
+}
+
 /// Main evaluation function - evaluates an AST node in the given environment
 pub fn eval(node: Node, env: Env) -> Object {
+    Evaluator::default().eval(node, env)
+}
+
+fn eval_node(node: Node, env: Env, ev: &Evaluator) -> Object {
     use crate::ast::Node::*;
     match node {
-        Program { statements } => eval_program(statements, env),
+        Program { statements } => eval_program(statements, env, ev),
         ExprStmt { expression } => expression
-            .map(|expr| eval(*expr, env))
+            .map(|expr| eval_node(*expr, env, ev))
             .unwrap_or(NULL),
         IntegerLiteral { value } => Object::Integer(value),
+        CharLiteral { value } => Object::Char(value),
         BooleanLiteral { value } => native_bool_to_boolean_object(value),
-        Prefix { operator, right } => eval_prefix(operator, right, env),
-        Infix { left, operator, right } => eval_infix(left, operator, right, env),
-        Block { statements } => eval_block(statements, env),
+        Prefix { operator, right } => eval_prefix(operator, right, env, ev),
+        Infix { left, operator, right } => eval_infix(left, operator, right, env, ev),
+        Block { statements } => eval_block(statements, env, ev),
         If { condition, consequence, alternative } => {
-            eval_if(condition, consequence, alternative, env)
+            eval_if(condition, consequence, alternative, env, ev)
         }
-        Return { return_value } => eval_return(return_value, env),
-        Let { name, value } => eval_let(name, value, env),
+        Return { return_value } => eval_return(return_value, env, ev),
+        Let {
+            name,
+            type_annotation,
+            value,
+        } => eval_let(name, type_annotation, value, env, ev),
+        Assign { name, value } => eval_assign(name, value, env, ev),
         Identifier { name } => eval_identifier(name, env),
         Function { parameters, body } => Object::Function {
+            name: None,
             parameters,
             body,
             env: Rc::clone(&env),
         },
-        Call { function, arguments } => eval_call(function, arguments, env),
+        Call { function, arguments } => eval_call(function, arguments, env, ev),
+        Match { subject, arms, default } => eval_match(subject, arms, default, env, ev),
     }
 }
 
-fn eval_program(statements: Vec<Node>, env: Env) -> Object {
+fn eval_program(statements: Vec<Node>, env: Env, ev: &Evaluator) -> Object {
     statements.into_iter().try_fold(NULL, |_, stmt| {
-        let result = eval(stmt, Rc::clone(&env));
+        let result = eval_node(stmt, Rc::clone(&env), ev);
         match &result {
             Object::ReturnValue(val) => Err(*val.clone()),
             Object::Error(_) => Err(result),
@@ -52,57 +245,111 @@ fn eval_program(statements: Vec<Node>, env: Env) -> Object {
     }).unwrap_or_else(|early_return| early_return)
 }
 
-fn eval_prefix(operator: String, right: Option<Box<Node>>, env: Env) -> Object {
+fn eval_prefix(operator: Operator, right: Option<Box<Node>>, env: Env, ev: &Evaluator) -> Object {
     let right_val = right
-        .map(|r| eval(*r, env))
+        .map(|r| eval_node(*r, env, ev))
         .unwrap_or(NULL);
     
-    if right_val.is_error() {
+    if interrupts(&right_val) {
         return right_val;
     }
     
-    match operator.as_str() {
-        "!" => eval_bang(right_val),
-        "-" => eval_minus_prefix(right_val),
+    match operator {
+        Operator::Bang => eval_bang(right_val),
+        Operator::Minus => eval_minus_prefix(right_val, ev),
         _ => Object::Error(format!("unknown operator: {}{}", operator, right_val.type_name())),
     }
 }
 
 fn eval_bang(right: Object) -> Object {
     match right {
-        TRUE => FALSE,
-        FALSE => TRUE,
-        NULL => TRUE,
+        Object::Boolean(value) => native_bool_to_boolean_object(!value),
+        Object::Null => TRUE,
         Object::Integer(_) => FALSE,
         _ => FALSE,
     }
 }
 
-fn eval_minus_prefix(right: Object) -> Object {
+fn eval_minus_prefix(right: Object, ev: &Evaluator) -> Object {
     match right {
-        Object::Integer(value) => Object::Integer(-value),
+        Object::Integer(value) if ev.wrapping_arithmetic => Object::Integer(value.wrapping_neg()),
+        // Only i64::MIN has no positive counterpart
+        Object::Integer(value) => value
+            .checked_neg()
+            .map(Object::Integer)
+            .unwrap_or_else(|| Object::Error(format!("integer overflow: -({})", value))),
         _ => Object::Error(format!("unknown operator: -{}", right.type_name())),
     }
 }
 
+/// Evaluates an infix expression, walking its left operands iteratively.
+///
+/// Left-associative chains such as `1 + 2 + ... + n` parse into a tree that is
+/// `n` levels deep on the left, so recursing into each left operand would
+/// overflow the stack on long chains.
 fn eval_infix(
     left: Option<Box<Node>>,
-    operator: String,
+    operator: Operator,
     right: Option<Box<Node>>,
     env: Env,
+    ev: &Evaluator,
 ) -> Object {
-    let left_val = left.map(|l| eval(*l, Rc::clone(&env))).unwrap_or(NULL);
-    if left_val.is_error() {
-        return left_val;
+    let mut pending = vec![(operator, right)];
+    let mut leftmost = left;
+    while let Some(node) = leftmost {
+        match *node {
+            Node::Infix { left, operator, right } => {
+                pending.push((operator, right));
+                leftmost = left;
+            }
+            other => {
+                leftmost = Some(Box::new(other));
+                break;
+            }
+        }
     }
-    
-    let right_val = right.map(|r| eval(*r, env)).unwrap_or(NULL);
-    if right_val.is_error() {
-        return right_val;
+
+    let left_val = leftmost.map(|l| eval_node(*l, Rc::clone(&env), ev)).unwrap_or(NULL);
+    pending.into_iter().rev().try_fold(left_val, |left_val, (operator, right)| {
+        if interrupts(&left_val) {
+            return Err(left_val);
+        }
+
+        if let Some(decided) = short_circuit(operator, &left_val) {
+            return Ok(decided);
+        }
+
+        let right_val = right.map(|r| eval_node(*r, Rc::clone(&env), ev)).unwrap_or(NULL);
+        if interrupts(&right_val) {
+            return Err(right_val);
+        }
+
+        Ok(apply_infix_operator(operator, left_val, right_val, ev))
+    }).unwrap_or_else(|interrupted| interrupted)
+}
+
+/// The result of `&&` or `||` when the left operand alone decides it, in which
+/// case the right operand must not be evaluated.
+fn short_circuit(operator: Operator, left_val: &Object) -> Option<Object> {
+    match (operator, left_val) {
+        (Operator::And, Object::Boolean(false)) => Some(FALSE),
+        (Operator::Or, Object::Boolean(true)) => Some(TRUE),
+        _ => None,
+    }
+}
+
+fn apply_infix_operator(operator: Operator, left_val: Object, right_val: Object, ev: &Evaluator) -> Object {
+    if ev.bool_coercion
+        && is_arithmetic_operator(operator)
+        && let (Some(l), Some(r)) = (coerce_to_integer(&left_val), coerce_to_integer(&right_val))
+    {
+        return eval_integer_infix(operator, l, r, ev);
     }
     
     match (&left_val, &right_val) {
-        (Object::Integer(l), Object::Integer(r)) => eval_integer_infix(operator, *l, *r),
+        (Object::Integer(l), Object::Integer(r)) => eval_integer_infix(operator, *l, *r, ev),
+        (Object::Char(c), Object::Integer(offset)) => eval_char_shift(operator, *c, *offset),
+        (Object::Char(l), Object::Char(r)) => eval_char_infix(operator, *l, *r),
         (Object::Boolean(l), Object::Boolean(r)) => eval_boolean_infix(operator, *l, *r),
         (l, r) if l.type_name() != r.type_name() => {
             Object::Error(format!("type mismatch: {} {} {}", l.type_name(), operator, r.type_name()))
@@ -114,31 +361,98 @@ fn eval_infix(
     }
 }
 
-fn eval_integer_infix(operator: String, left: i64, right: i64) -> Object {
-    match operator.as_str() {
-        "+" => Object::Integer(left + right),
-        "-" => Object::Integer(left - right),
-        "*" => Object::Integer(left * right),
-        "/" => Object::Integer(left / right),
-        "<" => native_bool_to_boolean_object(left < right),
-        ">" => native_bool_to_boolean_object(left > right),
-        "==" => native_bool_to_boolean_object(left == right),
-        "!=" => native_bool_to_boolean_object(left != right),
+fn is_arithmetic_operator(operator: Operator) -> bool {
+    matches!(
+        operator,
+        Operator::Plus | Operator::Minus | Operator::Asterisk | Operator::Slash | Operator::Percent
+    )
+}
+
+/// Integer view of an operand under bool coercion: booleans become `1`/`0`.
+fn coerce_to_integer(obj: &Object) -> Option<i64> {
+    match obj {
+        Object::Integer(value) => Some(*value),
+        Object::Boolean(value) => Some(i64::from(*value)),
+        _ => None,
+    }
+}
+
+fn eval_integer_infix(operator: Operator, left: i64, right: i64, ev: &Evaluator) -> Object {
+    let arithmetic = |checked: fn(i64, i64) -> Option<i64>, wrapping: fn(i64, i64) -> i64| {
+        if ev.wrapping_arithmetic {
+            return Object::Integer(wrapping(left, right));
+        }
+        checked(left, right).map(Object::Integer).unwrap_or_else(|| {
+            Object::Error(format!("integer overflow: {} {} {}", left, operator, right))
+        })
+    };
+
+    match operator {
+        Operator::Plus => arithmetic(i64::checked_add, i64::wrapping_add),
+        Operator::Minus => arithmetic(i64::checked_sub, i64::wrapping_sub),
+        Operator::Asterisk => arithmetic(i64::checked_mul, i64::wrapping_mul),
+        Operator::Slash if right == 0 => Object::Error("division by zero".to_string()),
+        Operator::Slash => arithmetic(i64::checked_div, i64::wrapping_div),
+        Operator::Percent if right == 0 => Object::Error("modulo by zero".to_string()),
+        Operator::Percent => arithmetic(i64::checked_rem, i64::wrapping_rem),
+        Operator::LessThan => native_bool_to_boolean_object(left < right),
+        Operator::GreaterThan => native_bool_to_boolean_object(left > right),
+        Operator::LessEq => native_bool_to_boolean_object(left <= right),
+        Operator::GreaterEq => native_bool_to_boolean_object(left >= right),
+        Operator::Eq => native_bool_to_boolean_object(left == right),
+        Operator::NotEq => native_bool_to_boolean_object(left != right),
         _ => Object::Error(format!("unknown operator: {} {} {}", left, operator, right)),
     }
 }
 
-fn eval_boolean_infix(operator: String, left: bool, right: bool) -> Object {
-    match operator.as_str() {
-        "==" => native_bool_to_boolean_object(left == right),
-        "!=" => native_bool_to_boolean_object(left != right),
+/// `char + int` / `char - int` move the character's code point by the integer.
+fn eval_char_shift(operator: Operator, c: char, offset: i64) -> Object {
+    let base = i64::from(u32::from(c));
+    let code_point = match operator {
+        Operator::Plus => base.checked_add(offset),
+        Operator::Minus => base.checked_sub(offset),
+        _ => return Object::Error(format!("unknown operator: CHAR {} INTEGER", operator)),
+    };
+    match code_point {
+        Some(code_point) => code_point_to_char(code_point),
+        None => Object::Error(format!("integer overflow: {} {} {}", char_literal(c), operator, offset)),
+    }
+}
+
+/// Converts an integer code point into an `Object::Char`, erroring for invalid code points.
+pub(crate) fn code_point_to_char(code_point: i64) -> Object {
+    u32::try_from(code_point)
+        .ok()
+        .and_then(char::from_u32)
+        .map(Object::Char)
+        .unwrap_or_else(|| Object::Error(format!("invalid code point: {}", code_point)))
+}
+
+fn eval_char_infix(operator: Operator, left: char, right: char) -> Object {
+    match operator {
+        Operator::LessThan => native_bool_to_boolean_object(left < right),
+        Operator::GreaterThan => native_bool_to_boolean_object(left > right),
+        Operator::LessEq => native_bool_to_boolean_object(left <= right),
+        Operator::GreaterEq => native_bool_to_boolean_object(left >= right),
+        Operator::Eq => native_bool_to_boolean_object(left == right),
+        Operator::NotEq => native_bool_to_boolean_object(left != right),
+        _ => Object::Error(format!("unknown operator: CHAR {} CHAR", operator)),
+    }
+}
+
+fn eval_boolean_infix(operator: Operator, left: bool, right: bool) -> Object {
+    match operator {
+        Operator::Eq => native_bool_to_boolean_object(left == right),
+        Operator::NotEq => native_bool_to_boolean_object(left != right),
+        Operator::And => native_bool_to_boolean_object(left && right),
+        Operator::Or => native_bool_to_boolean_object(left || right),
         _ => Object::Error(format!("unknown operator: BOOLEAN {} BOOLEAN", operator)),
     }
 }
 
-fn eval_block(statements: Vec<Node>, env: Env) -> Object {
+fn eval_block(statements: Vec<Node>, env: Env, ev: &Evaluator) -> Object {
     statements.into_iter().try_fold(NULL, |_, stmt| {
-        let result = eval(stmt, Rc::clone(&env));
+        let result = eval_node(stmt, Rc::clone(&env), ev);
         match &result {
             Object::ReturnValue(val) if !matches!(val.as_ref(), Object::Null) => Err(result),
             Object::Error(_) => Err(result),
@@ -152,25 +466,61 @@ fn eval_if(
     consequence: Option<Box<Node>>,
     alternative: Option<Box<Node>>,
     env: Env,
+    ev: &Evaluator,
 ) -> Object {
-    let cond_val = condition.map(|c| eval(*c, Rc::clone(&env))).unwrap_or(NULL);
+    let cond_val = condition.map(|c| eval_node(*c, Rc::clone(&env), ev)).unwrap_or(NULL);
     
-    if cond_val.is_error() {
+    if interrupts(&cond_val) {
         return cond_val;
     }
+
+    if ev.strict_conditions && !matches!(cond_val, Object::Boolean(_)) {
+        return Object::Error(format!("condition must be a boolean, got {}", cond_val.type_name()));
+    }
     
     if is_truthy(&cond_val) {
-        consequence.map(|c| eval(*c, env)).unwrap_or(NULL)
+        consequence.map(|c| eval_node(*c, env, ev)).unwrap_or(NULL)
     } else {
-        alternative.map(|a| eval(*a, env)).unwrap_or(NULL)
+        alternative.map(|a| eval_node(*a, env, ev)).unwrap_or(NULL)
+    }
+}
+
+fn eval_match(
+    subject: Option<Box<Node>>,
+    arms: Vec<(Node, Node)>,
+    default: Option<Box<Node>>,
+    env: Env,
+    ev: &Evaluator,
+) -> Object {
+    let subject_val = subject.map(|s| eval_node(*s, Rc::clone(&env), ev)).unwrap_or(NULL);
+    if interrupts(&subject_val) {
+        return subject_val;
+    }
+
+    for (pattern, value) in arms {
+        let pattern_val = eval_node(pattern, Rc::clone(&env), ev);
+        if interrupts(&pattern_val) {
+            return pattern_val;
+        }
+        if pattern_matches(&pattern_val, &subject_val) {
+            return eval_node(value, env, ev);
+        }
     }
+
+    default.map(|d| eval_node(*d, env, ev)).unwrap_or(NULL)
+}
+
+/// Patterns match by value. Functions never match, not even a structurally
+/// equal one, since a match arm is about values rather than code.
+fn pattern_matches(pattern: &Object, subject: &Object) -> bool {
+    !matches!(pattern, Object::Function { .. }) && pattern == subject
 }
 
-fn eval_return(return_value: Option<Box<Node>>, env: Env) -> Object {
+fn eval_return(return_value: Option<Box<Node>>, env: Env, ev: &Evaluator) -> Object {
     return_value
         .map(|val| {
-            let result = eval(*val, env);
-            if result.is_error() {
+            let result = eval_node(*val, env, ev);
+            if interrupts(&result) {
                 result
             } else {
                 Object::ReturnValue(Box::new(result))
@@ -179,46 +529,142 @@ fn eval_return(return_value: Option<Box<Node>>, env: Env) -> Object {
         .unwrap_or(NULL)
 }
 
-fn eval_let(name: Option<Box<Node>>, value: Option<Box<Node>>, env: Env) -> Object {
+fn eval_let(
+    name: Option<Box<Node>>,
+    type_annotation: Option<String>,
+    value: Option<Box<Node>>,
+    env: Env,
+    ev: &Evaluator,
+) -> Object {
     let name_str = match name.map(|n| *n) {
         Some(Node::Identifier { name }) => name,
         _ => return Object::Error("let statement name must be an identifier".to_string()),
     };
+
+    if env.borrow().get(&name_str).is_none()
+        && value.as_deref().is_some_and(|v| references_outside_functions(v, &name_str))
+    {
+        return Object::Error(format!("cannot reference '{}' in its own initializer", name_str));
+    }
     
-    let value_obj = value
-        .map(|v| eval(*v, Rc::clone(&env)))
+    let mut value_obj = value
+        .map(|v| eval_node(*v, Rc::clone(&env), ev))
         .unwrap_or(Object::Null);
     
-    if value_obj.is_error() {
+    if interrupts(&value_obj) {
         return value_obj;
     }
+
+    // `let add = fn...` names the function; rebinding an already named one keeps its name
+    if let Object::Function { name: fn_name @ None, .. } = &mut value_obj {
+        *fn_name = Some(name_str.clone());
+    }
+
+    if let Some(annotation) = type_annotation.filter(|_| ev.type_checks)
+        && !matches_type_annotation(&annotation, &value_obj)
+    {
+        return Object::Error(format!(
+            "type annotation mismatch: {} declared {} but got {}",
+            name_str,
+            annotation,
+            value_obj.type_name()
+        ));
+    }
     
-    env.borrow_mut().set(&name_str, value_obj.clone());
-    value_obj
+    match env.borrow_mut().set(&name_str, value_obj.clone()) {
+        Ok(()) => value_obj,
+        Err(error) => error,
+    }
+}
+
+/// Whether `node` uses the identifier `name` other than inside a function literal,
+/// where it is only looked up once the function is called (as in recursion).
+///
+/// The names bound by nested `let`s and assignments are targets, not uses. Blocks
+/// share the enclosing scope, so once a `let` in a block binds `name`, the rest of
+/// that block reads the new binding and isn't checked.
+fn references_outside_functions(node: &Node, name: &str) -> bool {
+    let binds_name = |stmt: &Node| {
+        matches!(stmt, Node::Let { name: Some(bound), .. } if matches!(&**bound, Node::Identifier { name: ident } if ident == name))
+    };
+
+    let mut pending = vec![node];
+    while let Some(node) = pending.pop() {
+        match node {
+            Node::Identifier { name: ident } if ident == name => return true,
+            Node::Function { .. } => {}
+            Node::Let { value, .. } | Node::Assign { value, .. } => pending.extend(value.as_deref()),
+            Node::Block { statements } => {
+                let checked = statements.iter().position(binds_name).map_or(statements.len(), |i| i + 1);
+                pending.extend(&statements[..checked]);
+            }
+            _ => pending.extend(node.children()),
+        }
+    }
+    false
+}
+
+fn matches_type_annotation(annotation: &str, value: &Object) -> bool {
+    match annotation {
+        "int" => matches!(value, Object::Integer(_)),
+        "bool" => matches!(value, Object::Boolean(_)),
+        "char" => matches!(value, Object::Char(_)),
+        "fn" => matches!(value, Object::Function { .. } | Object::Builtin(_)),
+        // float, string and array values don't exist yet, so nothing satisfies them
+        _ => false,
+    }
+}
+
+fn eval_assign(name: Option<Box<Node>>, value: Option<Box<Node>>, env: Env, ev: &Evaluator) -> Object {
+    let name_str = match name.map(|n| *n) {
+        Some(Node::Identifier { name }) => name,
+        _ => return Object::Error("assignment target must be an identifier".to_string()),
+    };
+
+    let value_obj = value
+        .map(|v| eval_node(*v, Rc::clone(&env), ev))
+        .unwrap_or(Object::Null);
+
+    if interrupts(&value_obj) {
+        return value_obj;
+    }
+
+    match env.borrow_mut().assign(&name_str, value_obj.clone()) {
+        Ok(()) => value_obj,
+        Err(error) => error,
+    }
 }
 
 fn eval_identifier(name: String, env: Env) -> Object {
     debug!("Evaluating identifier: {}", name);
     env.borrow()
         .get(&name)
+        .or_else(|| builtins::lookup(&name))
         .unwrap_or_else(|| Object::Error(format!("identifier not found: {}", name)))
 }
 
-fn eval_call(function: Option<Box<Node>>, arguments: Vec<Node>, env: Env) -> Object {
+fn eval_call(function: Option<Box<Node>>, arguments: Vec<Node>, env: Env, ev: &Evaluator) -> Object {
     let func = function
-        .map(|f| eval(*f, Rc::clone(&env)))
+        .map(|f| eval_node(*f, Rc::clone(&env), ev))
         .unwrap_or(NULL);
     
-    if func.is_error() {
+    if interrupts(&func) {
         return func;
     }
+
+    // Reject non-callable targets before evaluating any arguments
+    if !matches!(func, Object::Function { .. } | Object::Builtin(_)) {
+        return Object::Error(format!("not a function: {}", func.type_name()));
+    }
+
+    let passes_errors = matches!(&func, Object::Builtin(builtin) if builtin.accepts_errors);
     
-    // Evaluate arguments, short-circuit on error
+    // Evaluate arguments, short-circuit on error or return
     let args: Result<Vec<Object>, Object> = arguments
         .into_iter()
         .map(|arg| {
-            let result = eval(arg, Rc::clone(&env));
-            if result.is_error() {
+            let result = eval_node(arg, Rc::clone(&env), ev);
+            if interrupts(&result) && !(passes_errors && result.is_error()) {
                 Err(result)
             } else {
                 Ok(result)
@@ -228,37 +674,55 @@ fn eval_call(function: Option<Box<Node>>, arguments: Vec<Node>, env: Env) -> Obj
     
     match args {
         Err(err) => err,
-        Ok(args) => apply_function(func, args),
+        Ok(args) => apply_function(func, args, ev),
     }
 }
 
-fn apply_function(function: Object, args: Vec<Object>) -> Object {
+fn apply_function(function: Object, args: Vec<Object>, ev: &Evaluator) -> Object {
     match function {
-        Object::Function { parameters, body, env: func_env } => {
+        Object::Function { name, parameters, body, env: func_env } => {
+            if args.len() != parameters.len() {
+                let target = name.map(|n| format!(" to '{}'", n)).unwrap_or_default();
+                return Object::Error(format!(
+                    "wrong number of arguments{}. got={}, want={}",
+                    target,
+                    args.len(),
+                    parameters.len()
+                ));
+            }
+
             let extended_env = extend_function_env(&parameters, args, func_env);
             
             let result = body
-                .map(|b| eval(*b, extended_env))
+                .map(|b| eval_node(*b, extended_env, ev))
                 .unwrap_or(NULL);
             
             unwrap_return_value(result)
         }
+        Object::Builtin(builtin) => (builtin.func)(args, ev),
         _ => Object::Error(format!("not a function: {}", function.type_name())),
     }
 }
 
 fn extend_function_env(parameters: &[Node], args: Vec<Object>, outer: Env) -> Env {
-    let extended_env = Environment::new_enclosed(Rc::clone(&outer));
+    let extended_env = Environment::with_capacity(parameters.len(), Rc::clone(&outer));
     
-    for (param, arg) in parameters.iter().zip(args.into_iter()) {
+    for (param, arg) in parameters.iter().zip(args) {
         if let Node::Identifier { name } = param {
-            extended_env.borrow_mut().set(name, arg);
+            // A freshly created scope is never frozen
+            extended_env.borrow_mut().set(name, arg).ok();
         }
     }
     
     extended_env
 }
 
+/// Errors and `return` values abort the expression being evaluated and propagate
+/// outwards until a function call or the program unwraps them.
+fn interrupts(obj: &Object) -> bool {
+    matches!(obj, Object::Error(_) | Object::ReturnValue(_))
+}
+
 fn unwrap_return_value(obj: Object) -> Object {
     match obj {
         Object::ReturnValue(value) => *value,
@@ -278,9 +742,6 @@ fn native_bool_to_boolean_object(input: bool) -> Object {
 // TESTS
 // ============================================================================
 
-#[cfg(test)]
-use crate::{lexer::Lexer, parser::Parser};
-
 #[cfg(test)]
 use tracing_test::traced_test;
 
@@ -294,6 +755,16 @@ fn test_eval(input: &str) -> Object {
     eval(program, env)
 }
 
+/// Helper to run evaluation tests from source code with custom options
+#[cfg(test)]
+fn test_eval_with(input: &str, evaluator: &Evaluator) -> Object {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+    let env = Environment::new();
+    evaluator.eval(program, env)
+}
+
 #[rstest]
 #[case(Node::IntegerLiteral { value: 5 }, Object::Integer(5))]
 #[case(Node::IntegerLiteral { value: 10 }, Object::Integer(10))]
@@ -450,3 +921,638 @@ fn test_closures(#[case] input: &str, #[case] expected: Object) {
 fn test_deep_recursion(#[case] input: &str, #[case] expected: Object) {
     assert_eq!(test_eval(input), expected);
 }
+
+#[rstest]
+#[case("true + 1", "type mismatch: BOOLEAN + INTEGER")]
+#[case("1 - false", "type mismatch: INTEGER - BOOLEAN")]
+#[case("2 * true", "type mismatch: INTEGER * BOOLEAN")]
+#[case("true / true", "unknown operator: BOOLEAN / BOOLEAN")]
+fn test_bool_arithmetic_is_strict_by_default(#[case] input: &str, #[case] expected_message: &str) {
+    match test_eval(input) {
+        Object::Error(message) => assert_eq!(message, expected_message),
+        other => panic!("no error object returned. got={}", other),
+    }
+}
+
+#[rstest]
+#[case("true + 1", Object::Integer(2))]
+#[case("1 - false", Object::Integer(1))]
+#[case("2 * true", Object::Integer(2))]
+#[case("true + true", Object::Integer(2))]
+#[case("let t = true; t * 10 + false", Object::Integer(10))]
+#[case("true == 1", Object::Error("type mismatch: BOOLEAN == INTEGER".to_string()))]
+fn test_bool_coercion_mode(#[case] input: &str, #[case] expected: Object) {
+    let evaluator = Evaluator::new().with_bool_coercion(true);
+    assert_eq!(test_eval_with(input, &evaluator), expected);
+}
+
+#[rstest]
+#[case("5(1)", "not a function: INTEGER")]
+#[case("let x = 5; x(1)", "not a function: INTEGER")]
+#[case("true()", "not a function: BOOLEAN")]
+#[case("(1 < 2)(3)", "not a function: BOOLEAN")]
+#[case("if (false) { 1 }()", "not a function: NULL")]
+#[case("5(foobar)", "not a function: INTEGER")]
+fn test_calling_non_function(#[case] input: &str, #[case] expected_message: &str) {
+    match test_eval(input) {
+        Object::Error(message) => assert_eq!(message, expected_message),
+        other => panic!("no error object returned. got={}", other),
+    }
+}
+
+#[rstest]
+#[case("let a = 1; a = 2; a;", Object::Integer(2))]
+#[case("let a = 0; let b = 0; a = b = 5; a + b;", Object::Integer(10))]
+#[case("let a = 0; a = 1 + 2 * 3;", Object::Integer(7))]
+#[case("let x = 1; let set = fn(v) { x = v; }; set(2); x;", Object::Integer(2))]
+#[case("let x = 1; let shadow = fn(x) { x = 5; }; shadow(0); x;", Object::Integer(1))]
+#[case("y = 1;", Object::Error("identifier not found: y".to_string()))]
+fn test_assignment(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("is_nil(if (false) { 1 })", TRUE)]
+#[case("is_nil(0)", FALSE)]
+#[case("is_error(foobar)", TRUE)]
+#[case("is_error(1 + true)", TRUE)]
+#[case("is_error(1)", FALSE)]
+#[case("is_int(5)", TRUE)]
+#[case("is_int(true)", FALSE)]
+#[case("is_bool(1 < 2)", TRUE)]
+#[case("is_bool(0)", FALSE)]
+#[case("is_fn(fn(x) { x })", TRUE)]
+#[case("is_fn(is_fn)", TRUE)]
+#[case("is_fn(1)", FALSE)]
+#[case("let is_int = fn(x) { 42 }; is_int(true)", Object::Integer(42))]
+#[case("is_int(1, 2)", Object::Error("wrong number of arguments. got=2, want=1".to_string()))]
+fn test_reflection_builtins(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("5 - -3", 8)]
+#[case("5--3", 8)]
+#[case("--5", 5)]
+#[case("-(-5)", 5)]
+#[case("---5", -5)]
+#[case("-5 - -5", 0)]
+fn test_stacked_minus(#[case] input: &str, #[case] expected: i64) {
+    assert_eq!(test_eval(input), Object::Integer(expected));
+}
+
+#[rstest]
+fn test_prelude_parses_cleanly() {
+    let mut lexer = Lexer::new(PRELUDE);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 0);
+}
+
+#[rstest]
+#[case("identity(7)", Object::Integer(7))]
+#[case("abs(-5)", Object::Integer(5))]
+#[case("abs(5)", Object::Integer(5))]
+#[case("min(3, 4)", Object::Integer(3))]
+#[case("max(3, 4)", Object::Integer(4))]
+#[case("compose(abs, fn(x) { x - 10 })(3)", Object::Integer(7))]
+fn test_prelude_functions(#[case] input: &str, #[case] expected: Object) {
+    let evaluator = Evaluator::new().with_prelude();
+    let mut lexer = Lexer::new(input);
+    let program = Parser::new(&mut lexer).parse_program();
+
+    assert_eq!(evaluator.eval(program, evaluator.environment()), expected);
+}
+
+#[rstest]
+fn test_without_prelude() {
+    let evaluator = Evaluator::new().without_prelude();
+    let mut lexer = Lexer::new("abs(-5)");
+    let program = Parser::new(&mut lexer).parse_program();
+
+    assert_eq!(
+        evaluator.eval(program, evaluator.environment()),
+        Object::Error("identifier not found: abs".to_string())
+    );
+}
+
+#[rstest]
+#[case("'a'", Object::Char('a'))]
+#[case("'\\n'", Object::Char('\n'))]
+#[case("'a' + 1", Object::Char('b'))]
+#[case("'b' - 1", Object::Char('a'))]
+#[case("'a' == 'a'", TRUE)]
+#[case("'a' != 'a'", FALSE)]
+#[case("'a' < 'b'", TRUE)]
+#[case("int('A')", Object::Integer(65))]
+#[case("char(66)", Object::Char('B'))]
+#[case("char(2 + int('a'))", Object::Char('c'))]
+#[case("'a' - 98", Object::Error("invalid code point: -1".to_string()))]
+#[case("'a' + 9223372036854775807", Object::Error("integer overflow: 'a' + 9223372036854775807".to_string()))]
+#[case("'a' - (0 - 9223372036854775807 - 1)", Object::Error("integer overflow: 'a' - -9223372036854775808".to_string()))]
+#[case("'a' - 9223372036854775807", Object::Error("invalid code point: -9223372036854775710".to_string()))]
+#[case("char(55296)", Object::Error("invalid code point: 55296".to_string()))]
+#[case("'a' + true", Object::Error("type mismatch: CHAR + BOOLEAN".to_string()))]
+#[case("1 + 'a'", Object::Error("type mismatch: INTEGER + CHAR".to_string()))]
+#[case("'a' * 'b'", Object::Error("unknown operator: CHAR * CHAR".to_string()))]
+#[case("int(true)", Object::Error("argument to `int` not supported, got BOOLEAN".to_string()))]
+fn test_char_expressions(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("let five = fn() { 5 }; five();", Object::Integer(5))]
+#[case("let x = 1; let bump = fn() { x = x + 1; }; bump(); bump(); x;", Object::Integer(3))]
+fn test_parameterless_functions(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("let sum = fn(n, acc) { if (n == 0) { acc } else { sum(n - 1, acc + n) } }; sum(100, 0);", Object::Integer(5050))]
+#[case("let pick = fn(a, b, c, d) { if (a == 0) { d } else { pick(a - 1, c, d, b) } }; pick(9, 1, 2, 3);", Object::Integer(3))]
+fn test_recursion_with_presized_environments(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("let x: int = true; x;", Object::Boolean(true))]
+#[case("let x: bool = 5; x;", Object::Integer(5))]
+fn test_type_annotations_ignored_by_default(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("let x: int = 5; x;", Object::Integer(5))]
+#[case("let b: bool = 1 < 2; b;", TRUE)]
+#[case("let c: char = 'c'; c;", Object::Char('c'))]
+#[case("let f: fn = fn(x) { x }; f(3);", Object::Integer(3))]
+#[case("let g: fn = is_int; g(3);", TRUE)]
+#[case(
+    "let x: int = true; x;",
+    Object::Error("type annotation mismatch: x declared int but got BOOLEAN".to_string())
+)]
+#[case(
+    "let s: string = 5;",
+    Object::Error("type annotation mismatch: s declared string but got INTEGER".to_string())
+)]
+fn test_type_checks(#[case] input: &str, #[case] expected: Object) {
+    let evaluator = Evaluator::new().with_type_checks(true);
+    assert_eq!(test_eval_with(input, &evaluator), expected);
+}
+
+#[rstest]
+#[case("9223372036854775807 + 1", "integer overflow: 9223372036854775807 + 1")]
+#[case("0 - 9223372036854775807 - 2", "integer overflow: -9223372036854775807 - 2")]
+#[case("9223372036854775807 * 2", "integer overflow: 9223372036854775807 * 2")]
+#[case("(0 - 9223372036854775807 - 1) / (0 - 1)", "integer overflow: -9223372036854775808 / -1")]
+#[case("5 / 0", "division by zero")]
+#[case("(0 - 9223372036854775807 - 1) % (0 - 1)", "integer overflow: -9223372036854775808 % -1")]
+#[case("5 % 0", "modulo by zero")]
+fn test_checked_arithmetic(#[case] input: &str, #[case] expected_message: &str) {
+    assert_eq!(test_eval(input), Object::Error(expected_message.to_string()));
+}
+
+#[rstest]
+#[case("9223372036854775807 + 1", Object::Integer(i64::MIN))]
+#[case("0 - 9223372036854775807 - 2", Object::Integer(i64::MAX))]
+#[case("9223372036854775807 * 2", Object::Integer(-2))]
+#[case("(0 - 9223372036854775807 - 1) / (0 - 1)", Object::Integer(i64::MIN))]
+#[case("5 / 0", Object::Error("division by zero".to_string()))]
+#[case("(0 - 9223372036854775807 - 1) % (0 - 1)", Object::Integer(0))]
+#[case("5 % 0", Object::Error("modulo by zero".to_string()))]
+#[case("2 + 3 * 4", Object::Integer(14))]
+fn test_wrapping_arithmetic(#[case] input: &str, #[case] expected: Object) {
+    let evaluator = Evaluator::new().with_wrapping_arithmetic(true);
+    assert_eq!(test_eval_with(input, &evaluator), expected);
+}
+
+#[rstest]
+#[case("if (5) { 10 } else { 20 }", false, Object::Integer(10))]
+#[case("if (5) { 10 } else { 20 }", true, Object::Error("condition must be a boolean, got INTEGER".to_string()))]
+#[case("if (1 < 2) { 10 } else { 20 }", true, Object::Integer(10))]
+#[case("if (fn() { 1 }) { 10 }", true, Object::Error("condition must be a boolean, got FUNCTION_OBJ".to_string()))]
+#[case("if (-true) { 10 }", true, Object::Error("unknown operator: -BOOLEAN".to_string()))]
+fn test_strict_conditions(#[case] input: &str, #[case] strict: bool, #[case] expected: Object) {
+    let evaluator = Evaluator::new().with_strict_conditions(strict);
+    assert_eq!(test_eval_with(input, &evaluator), expected);
+}
+
+#[rstest]
+#[case("let x = if (true) { return 5; }; x + 100;", Object::Integer(5))]
+#[case("let f = fn() { let x = if (true) { return 5; }; x + 100 }; f() + 1;", Object::Integer(6))]
+#[case("let f = fn() { (if (true) { return 5; }) == 5 }; f();", Object::Integer(5))]
+#[case("let f = fn() { -if (true) { return 5; } }; f();", Object::Integer(5))]
+#[case("let f = fn() { is_int(if (true) { return 5; }) == false }; f();", Object::Integer(5))]
+#[case("let f = fn() { is_error(if (true) { return 5; }) }; f();", Object::Integer(5))]
+#[case("let f = fn() { if (if (true) { return 5; }) { 1 } else { 2 } }; f();", Object::Integer(5))]
+fn test_return_values_do_not_escape(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("return 5;")]
+#[case("if (true) { if (true) { return 5; } }")]
+#[case("let f = fn() { return 5; }; f();")]
+fn test_program_unwraps_return_values(#[case] input: &str) {
+    let result = test_eval(input);
+    assert_eq!(result, Object::Integer(5));
+
+    // Inspecting a wrapped value is transparent, comparing it is not
+    let wrapped = Object::ReturnValue(Box::new(result.clone()));
+    assert_eq!(wrapped.inspect(), result.inspect());
+    assert_ne!(wrapped, result);
+}
+
+#[rstest]
+#[case("dbg(5);", Object::Integer(5), "dbg: 5\n")]
+#[case("let y = 1 + dbg(2 * 3); y;", Object::Integer(7), "dbg: 6\n")]
+#[case("let c = dbg('a'); dbg(c + 1);", Object::Char('b'), "dbg: 'a'\ndbg: 'b'\n")]
+#[case("is_error(dbg(missing));", TRUE, "dbg: identifier not found: missing\n")]
+#[case(
+    "dbg(1, 2);",
+    Object::Error("wrong number of arguments. got=2, want=1".to_string()),
+    ""
+)]
+fn test_dbg(#[case] input: &str, #[case] expected: Object, #[case] expected_output: &str) {
+    let buffer = Rc::new(RefCell::new(Vec::new()));
+    let evaluator = Evaluator::new().with_output(buffer.clone());
+
+    assert_eq!(test_eval_with(input, &evaluator), expected);
+    assert_eq!(String::from_utf8_lossy(&buffer.borrow()), expected_output);
+}
+
+#[rstest]
+#[case("clamp(0 - 5, 0, 10);", Object::Integer(0))]
+#[case("clamp(5, 0, 10);", Object::Integer(5))]
+#[case("clamp(15, 0, 10);", Object::Integer(10))]
+#[case("clamp(3, 3, 3);", Object::Integer(3))]
+#[case("clamp(5, 10, 0);", Object::Error("invalid range for `clamp`: 10 > 0".to_string()))]
+#[case("clamp(true, 0, 10);", Object::Error("argument to `clamp` not supported, got BOOLEAN".to_string()))]
+#[case("clamp(5, 'a', 10);", Object::Error("argument to `clamp` not supported, got CHAR".to_string()))]
+#[case("clamp(5, 0);", Object::Error("wrong number of arguments. got=2, want=3".to_string()))]
+fn test_clamp(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("gcd(12, 18);", Object::Integer(6))]
+#[case("gcd(0 - 12, 18);", Object::Integer(6))]
+#[case("gcd(7, 0);", Object::Integer(7))]
+#[case("gcd(0, 0);", Object::Integer(0))]
+#[case(
+    "gcd(0 - 9223372036854775807 - 1, 0);",
+    Object::Error("integer overflow: gcd(-9223372036854775808, 0)".to_string())
+)]
+#[case("lcm(4, 6);", Object::Integer(12))]
+#[case("lcm(0 - 4, 6);", Object::Integer(12))]
+#[case("lcm(0, 5);", Object::Integer(0))]
+#[case(
+    "lcm(9223372036854775807, 2);",
+    Object::Error("integer overflow: lcm(9223372036854775807, 2)".to_string())
+)]
+#[case("pow(2, 10);", Object::Integer(1024))]
+#[case("pow(0 - 3, 3);", Object::Integer(-27))]
+#[case("pow(5, 0);", Object::Integer(1))]
+#[case("pow(0, 0);", Object::Integer(1))]
+#[case("pow(0, 4294967296);", Object::Integer(0))]
+#[case("pow(1, 4294967296);", Object::Integer(1))]
+#[case("pow(0 - 1, 4294967296);", Object::Integer(1))]
+#[case("pow(0 - 1, 4294967297);", Object::Integer(-1))]
+#[case("pow(2, 63);", Object::Error("integer overflow: pow(2, 63)".to_string()))]
+#[case(
+    "pow(2, 4294967296);",
+    Object::Error("integer overflow: pow(2, 4294967296)".to_string())
+)]
+#[case(
+    "pow(2, 0 - 1);",
+    Object::Error("exponent to `pow` must not be negative, got -1".to_string())
+)]
+#[case("gcd(4, true);", Object::Error("argument to `gcd` not supported, got BOOLEAN".to_string()))]
+#[case("lcm('a', 2);", Object::Error("argument to `lcm` not supported, got CHAR".to_string()))]
+#[case("pow(2);", Object::Error("wrong number of arguments. got=1, want=2".to_string()))]
+fn test_integer_math_builtins(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("let add = fn(a, b) { a + b }; add;", Some(2), Some("add"))]
+#[case("let add = fn(a, b) { a + b }; let plus = add; plus;", Some(2), Some("add"))]
+#[case("fn() { 1 };", Some(0), None)]
+#[case("let make = fn() { fn(x) { x } }; make();", Some(1), None)]
+#[case("clamp;", Some(3), Some("clamp"))]
+#[case("5;", None, None)]
+fn test_arity_and_name(
+    #[case] input: &str,
+    #[case] expected_arity: Option<usize>,
+    #[case] expected_name: Option<&str>,
+) {
+    let result = test_eval(input);
+    assert_eq!(result.arity(), expected_arity);
+    assert_eq!(result.name(), expected_name);
+}
+
+#[rstest]
+#[case(
+    "let add = fn(a, b) { a + b }; add(1);",
+    "wrong number of arguments to 'add'. got=1, want=2"
+)]
+#[case("fn(a) { a }(1, 2);", "wrong number of arguments. got=2, want=1")]
+fn test_function_argument_count(#[case] input: &str, #[case] expected_message: &str) {
+    assert_eq!(test_eval(input), Object::Error(expected_message.to_string()));
+}
+
+#[rstest]
+#[case("let x = 5\nlet y = x * 2\ny", Object::Integer(10))]
+#[case("let f = fn(x) { return x + 1 }\nf(1)", Object::Integer(2))]
+#[case("let f = fn() { return }\nf()", NULL)]
+fn test_semicolons_are_optional(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("let curry = fn(x) { fn(y) { x + y } }; curry(1)(2);", Object::Integer(3))]
+#[case("let k = fn(a) { fn(b) { fn(c) { a * b + c } } }; k(2)(3)(4);", Object::Integer(10))]
+#[case("let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(10);", Object::Integer(55))]
+fn test_chained_calls(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case(Evaluator::new())]
+#[case(Evaluator::new().with_sandbox(true))]
+fn test_sleep_returns_null(#[case] evaluator: Evaluator) {
+    assert_eq!(test_eval_with("sleep(0);", &evaluator), NULL);
+    assert_eq!(
+        test_eval_with("sleep(0 - 1);", &evaluator),
+        Object::Error("argument to `sleep` must not be negative, got -1".to_string())
+    );
+}
+
+#[rstest]
+fn test_time_ns_is_monotonic() {
+    let result = test_eval("let a = time_ns(); sleep(1); let b = time_ns(); b - a;");
+    match result {
+        Object::Integer(elapsed) => assert!(elapsed >= 1_000_000, "elapsed: {}", elapsed),
+        other => panic!("expected an integer, got {:?}", other),
+    }
+    assert_eq!(test_eval("let a = time_ns(); let b = time_ns(); b < a;"), FALSE);
+}
+
+#[rstest]
+fn test_time_ns_disabled_in_sandbox() {
+    let evaluator = Evaluator::new().with_sandbox(true);
+    assert_eq!(
+        test_eval_with("time_ns();", &evaluator),
+        Object::Error("`time_ns` is not available in sandbox mode".to_string())
+    );
+}
+
+#[rstest]
+#[case("let add = fn(a, b) { a + b }; add;", "cannot serialize FUNCTION_OBJ value add")]
+#[case("fn(x) { x };", "cannot serialize FUNCTION_OBJ value <anonymous>")]
+#[case("clamp;", "cannot serialize BUILTIN value clamp")]
+#[case("missing;", "cannot serialize an error: identifier not found: missing")]
+fn test_functions_are_not_serializable(#[case] input: &str, #[case] expected_message: &str) {
+    assert_eq!(test_eval(input).to_serializable(), Err(expected_message.to_string()));
+}
+
+#[rstest]
+#[case("puts(1); puts(2);", "1\n2\n")]
+#[case("print(1); print(2); print('!');", "12!")]
+#[case("print('h'); print('i'); puts('!');", "hi!\n")]
+#[case("puts('\\n', true);", "\n\ntrue\n")]
+#[case("print(1, 2); puts(3, 4);", "123\n4\n")]
+#[case("print(1); puts(2); print(3);", "12\n3")]
+#[case("puts();", "")]
+fn test_print_and_puts(#[case] input: &str, #[case] expected_output: &str) {
+    let buffer = Rc::new(RefCell::new(Vec::new()));
+    let evaluator = Evaluator::new().with_output(buffer.clone());
+
+    assert_eq!(test_eval_with(input, &evaluator), NULL);
+    assert_eq!(String::from_utf8_lossy(&buffer.borrow()), expected_output);
+}
+
+#[rstest]
+#[case("-(0 - 9223372036854775807 - 1)", Object::Error("integer overflow: -(-9223372036854775808)".to_string()))]
+#[case("-(0 - 9223372036854775807)", Object::Integer(i64::MAX))]
+#[case("0 - 9223372036854775807 - 1", Object::Integer(i64::MIN))]
+fn test_negation_overflow(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+fn test_wrapping_negation() {
+    let evaluator = Evaluator::new().with_wrapping_arithmetic(true);
+    assert_eq!(
+        test_eval_with("-(0 - 9223372036854775807 - 1)", &evaluator),
+        Object::Integer(i64::MIN)
+    );
+}
+
+#[rstest]
+#[case(Object::Integer(i64::MIN), "-9223372036854775808")]
+#[case(Object::Integer(i64::MAX), "9223372036854775807")]
+#[case(Object::Integer(1_000_000), "1000000")]
+fn test_integer_inspect_is_plain(#[case] obj: Object, #[case] expected: &str) {
+    assert_eq!(obj.inspect(), expected);
+}
+
+#[rstest]
+#[case("1 + 2 * 3", Object::Integer(7))]
+#[case("x * 2;", Object::Integer(20))]
+#[case("1 + 2; 3", Object::Error("expected a single expression, got 2 statements".to_string()))]
+#[case("", Object::Error("expected a single expression, got 0 statements".to_string()))]
+#[case("let y = 1", Object::Error("expected an expression, got a Let statement".to_string()))]
+#[case("return 1", Object::Error("expected an expression, got a Return statement".to_string()))]
+#[case("1 +", Object::Error("syntax error: Expected expression after infix operator".to_string()))]
+fn test_eval_expr(#[case] input: &str, #[case] expected: Object) {
+    let env = Environment::new();
+    env.borrow_mut().set("x", Object::Integer(10)).unwrap();
+
+    assert_eq!(Evaluator::new().eval_expr(input, env), expected);
+}
+
+#[rstest]
+#[case("x + 1;", Object::Integer(2))]
+#[case("let y = 2;", Object::Error("environment is frozen".to_string()))]
+#[case("x = 2;", Object::Error("environment is frozen".to_string()))]
+#[case("let f = fn(a) { let b = a + x; b }; 1;", Object::Error("environment is frozen".to_string()))]
+#[case("fn(a) { let b = a + x; b }(1);", Object::Integer(2))]
+fn test_eval_in_frozen_environment(#[case] input: &str, #[case] expected: Object) {
+    let env = Environment::new();
+    env.borrow_mut().set("x", Object::Integer(1)).unwrap();
+    env.borrow_mut().freeze();
+
+    let mut lexer = Lexer::new(input);
+    let program = Parser::new(&mut lexer).parse_program();
+    assert_eq!(eval(program, env), expected);
+}
+
+#[rstest]
+#[case("+", 10_000)]
+#[case("-", 1 - 9_999)]
+#[case("*", 1)]
+fn test_eval_long_operator_chain(#[case] operator: &str, #[case] expected: i64) {
+    let input = vec!["1"; 10_000].join(&format!(" {} ", operator));
+    assert_eq!(test_eval(&input), Object::Integer(expected));
+}
+
+#[rstest]
+#[case("1 + 2 + true + 3", Object::Error("type mismatch: INTEGER + BOOLEAN".to_string()))]
+#[case("1 + 2 + missing + 3", Object::Error("identifier not found: missing".to_string()))]
+#[case("let f = fn() { 1 + if (true) { return 5; } + 2 }; f();", Object::Integer(5))]
+#[case("10 - 2 - 3 * 2 - 1", Object::Integer(1))]
+fn test_eval_operator_chain_interrupts(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("match 2 { 1 => 10, 2 => 20, _ => 0 }", Object::Integer(20))]
+#[case("match 5 { 1 => 10, 2 => 20, _ => 0 }", Object::Integer(0))]
+#[case("match 5 { 1 => 10, 2 => 20 }", Object::Null)]
+#[case("match 'b' { 'a' => 1, 'b' => 2 }", Object::Integer(2))]
+#[case("match 1 < 2 { false => 0, true => 1 }", Object::Integer(1))]
+#[case("let x = 3; match x * 2 { x + 3 => 1, _ => 2 }", Object::Integer(1))]
+#[case("match 1 { 1 => 10, 1 => 20 }", Object::Integer(10))]
+#[case("match 1 { true => 10, _ => 20 }", Object::Integer(20))]
+#[case("match missing { _ => 1 }", Object::Error("identifier not found: missing".to_string()))]
+#[case("match 2 { 1 => missing, 2 => 20 }", Object::Integer(20))]
+#[case("let f = fn(x) { match x { 0 => 1, _ => x * 2 } }; f(0) + f(5)", Object::Integer(11))]
+fn test_eval_match(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("let x = x + 1;", Object::Error("cannot reference 'x' in its own initializer".to_string()))]
+#[case("let x = -x;", Object::Error("cannot reference 'x' in its own initializer".to_string()))]
+#[case("let f = fn(n) { n }(f);", Object::Error("cannot reference 'f' in its own initializer".to_string()))]
+#[case("let x = 1; let x = x + 1; x", Object::Integer(2))]
+#[case("let f = fn(n) { if (n == 0) { 0 } else { f(n - 1) } }; f(3)", Object::Integer(0))]
+#[case("let x = y + 1;", Object::Error("identifier not found: y".to_string()))]
+#[case("let f = fn() { let x = 2; let y = x; y }; f()", Object::Integer(2))]
+#[case("let y = if (true) { let y = 2; 3 } else { 0 }; y", Object::Integer(3))]
+#[case("let y = if (true) { let y = 2; y + 1 } else { 0 }; y", Object::Integer(3))]
+#[case("let y = if (true) { let z = y; let y = 2; z } else { 0 };", Object::Error("cannot reference 'y' in its own initializer".to_string()))]
+#[case("let y = if (true) { let y = y; y } else { 0 };", Object::Error("cannot reference 'y' in its own initializer".to_string()))]
+#[case("let y = if (true) { y = 2 } else { 0 };", Object::Error("identifier not found: y".to_string()))]
+fn test_self_referential_let(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("fn(){}()", Object::Null)]
+#[case("let f = fn(x) {}; f(1)", Object::Null)]
+#[case("let f = fn() {}; is_nil(f())", Object::Boolean(true))]
+#[case("fn(x) {}()", Object::Error("wrong number of arguments. got=0, want=1".to_string()))]
+fn test_empty_function_body(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("fn(){}", "fn() {}")]
+#[case("fn(a, b) {}", "fn(a, b) {}")]
+fn test_empty_function_inspect(#[case] input: &str, #[case] expected: &str) {
+    assert_eq!(test_eval(input).inspect(), expected);
+}
+
+#[rstest]
+#[case("let a = 1, b = a + 1; b", Object::Integer(2))]
+#[case("let a = 1, b = a + 1, c = a + b; c", Object::Integer(3))]
+#[case("let a = 1, b = missing, c = 3; c", Object::Error("identifier not found: missing".to_string()))]
+fn test_eval_let_multiple_bindings(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+fn test_failed_binding_keeps_earlier_ones() {
+    let env = Environment::new();
+    let mut lexer = Lexer::new("let a = 1, b = 5 / 0, c = 3;");
+    let program = Parser::new(&mut lexer).parse_program();
+
+    assert_eq!(eval(program, Rc::clone(&env)), Object::Error("division by zero".to_string()));
+    assert_eq!(env.borrow().get("a"), Some(Object::Integer(1)));
+    assert_eq!(env.borrow().get("b"), None);
+    assert_eq!(env.borrow().get("c"), None);
+}
+
+#[rstest]
+#[case("1 + true; let x = 2; missing; x * 10", Object::Integer(20), vec!["type mismatch: INTEGER + BOOLEAN", "identifier not found: missing"])]
+#[case("let x = 1; x", Object::Integer(1), vec![])]
+#[case("5 / 0; -true", Object::Null, vec!["division by zero", "unknown operator: -BOOLEAN"])]
+#[case("missing; return 3; 5 / 0", Object::Integer(3), vec!["identifier not found: missing"])]
+fn test_eval_collecting_errors(#[case] input: &str, #[case] value: Object, #[case] errors: Vec<&str>) {
+    let mut lexer = Lexer::new(input);
+    let program = Parser::new(&mut lexer).parse_program();
+    let evaluator = Evaluator::new();
+
+    let (result, collected) = evaluator.eval_collecting_errors(program, evaluator.environment());
+
+    assert_eq!(result, value);
+    assert_eq!(collected, errors);
+}
+
+#[rstest]
+#[case("3 >= 3", Object::Boolean(true))]
+#[case("3 >= 4", Object::Boolean(false))]
+#[case("4 >= 3", Object::Boolean(true))]
+#[case("3 <= 3", Object::Boolean(true))]
+#[case("4 <= 3", Object::Boolean(false))]
+#[case("0 - 1 <= 0", Object::Boolean(true))]
+#[case("1 <= 2 == true", Object::Boolean(true))]
+#[case("'a' <= 'b'", Object::Boolean(true))]
+#[case("'b' >= 'c'", Object::Boolean(false))]
+#[case("true >= false", Object::Error("unknown operator: BOOLEAN >= BOOLEAN".to_string()))]
+#[case("1 <= true", Object::Error("type mismatch: INTEGER <= BOOLEAN".to_string()))]
+fn test_less_eq_greater_eq(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("true && true", Object::Boolean(true))]
+#[case("true && false", Object::Boolean(false))]
+#[case("false || true", Object::Boolean(true))]
+#[case("false || false", Object::Boolean(false))]
+#[case("1 < 2 && 2 < 3", Object::Boolean(true))]
+#[case("1 > 2 || 2 > 3", Object::Boolean(false))]
+#[case("false || true && false", Object::Boolean(false))]
+#[case("1 && true", Object::Error("type mismatch: INTEGER && BOOLEAN".to_string()))]
+#[case("true || 1", Object::Boolean(true))]
+#[case("true && 1", Object::Error("type mismatch: BOOLEAN && INTEGER".to_string()))]
+#[case("1 || 2", Object::Error("unknown operator: 1 || 2".to_string()))]
+fn test_logical_operators(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+#[case("false && undefined", Object::Boolean(false))]
+#[case("true || undefined", Object::Boolean(true))]
+#[case("1 > 2 && undefined(1, 2)", Object::Boolean(false))]
+#[case("false && undefined && also_undefined", Object::Boolean(false))]
+#[case("true && undefined", Object::Error("identifier not found: undefined".to_string()))]
+#[case("false || undefined", Object::Error("identifier not found: undefined".to_string()))]
+fn test_logical_operators_short_circuit(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
+
+#[rstest]
+fn test_short_circuit_skips_side_effects() {
+    let buffer = Rc::new(RefCell::new(Vec::new()));
+    let evaluator = Evaluator::new().with_output(buffer.clone());
+
+    assert_eq!(test_eval_with("let r = false && puts(1); puts(2); r", &evaluator), FALSE);
+    assert_eq!(String::from_utf8_lossy(&buffer.borrow()), "2\n");
+}
+
+#[rstest]
+#[case("10 % 3", Object::Integer(1))]
+#[case("10 % 2", Object::Integer(0))]
+#[case("5 % 0", Object::Error("modulo by zero".to_string()))]
+#[case("(0 - 7) % 3", Object::Integer(-1))]
+#[case("7 % (0 - 3)", Object::Integer(1))]
+#[case("1 + 10 % 4 * 2", Object::Integer(5))]
+#[case("let even = fn(n) { n % 2 == 0 }; even(4)", Object::Boolean(true))]
+#[case("'a' % 2", Object::Error("unknown operator: CHAR % INTEGER".to_string()))]
+fn test_modulo(#[case] input: &str, #[case] expected: Object) {
+    assert_eq!(test_eval(input), expected);
+}
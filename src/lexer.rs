@@ -1,11 +1,19 @@
 use rstest::rstest;
 
-use crate::token::{Token, lookup_ident};
+use crate::token::{Position, Token, lookup_ident};
+#[derive(Clone)]
 pub struct Lexer<'a> {
     input: &'a str,
     position: usize,
     read_position: usize,
     ch: char,
+    line: usize,
+    column: usize,
+    token_position: Position,
+    max_tokens: Option<usize>,
+    token_count: usize,
+    newline_terminators: bool,
+    paren_depth: usize,
 }
 
 impl<'a> Lexer<'a> {
@@ -15,12 +23,59 @@ impl<'a> Lexer<'a> {
             position: 0,
             read_position: 0,
             ch: '\0',
+            line: 1,
+            column: 0,
+            token_position: Position::default(),
+            max_tokens: None,
+            token_count: 0,
+            newline_terminators: false,
+            paren_depth: 0,
         };
         lexer.read_char();
         lexer
     }
 
+    /// Caps the number of tokens this lexer will produce.
+    ///
+    /// Once `max_tokens` tokens have been returned every further call to
+    /// `next_token` yields `Token::Eof`, which stops the parser on pathological input.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Emits `Token::Newline` for line breaks so they can end statements like `;`.
+    ///
+    /// Line breaks inside parentheses are still skipped, letting argument lists and
+    /// grouped expressions span several lines. Consecutive blank lines yield one token.
+    pub fn with_newline_terminators(mut self, enabled: bool) -> Self {
+        self.newline_terminators = enabled;
+        self
+    }
+
+    /// Restarts the lexer on `new_input`, keeping its configuration (token budget,
+    /// newline mode) but forgetting everything read so far.
+    pub fn reset(&mut self, new_input: &'a str) {
+        self.input = new_input;
+        self.position = 0;
+        self.read_position = 0;
+        self.ch = '\0';
+        self.line = 1;
+        self.column = 0;
+        self.token_position = Position::default();
+        self.token_count = 0;
+        self.paren_depth = 0;
+        self.read_char();
+    }
+
     fn read_char(&mut self) {
+        if self.ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
         if self.read_position >= self.input.len() {
             self.ch = '\0';
         } else {
@@ -39,14 +94,45 @@ impl<'a> Lexer<'a> {
     }
 
     pub fn next_token(&mut self) -> Token {
+        if self.max_tokens.is_some_and(|max| self.token_count >= max) {
+            return Token::Eof;
+        }
+        self.token_count += 1;
+
+        self.read_token()
+    }
+
+    /// Like [`Lexer::next_token`], also returning where the token starts.
+    pub fn next_positioned(&mut self) -> (Token, Position) {
+        let token = self.next_token();
+        (token, self.token_position)
+    }
+
+    /// Where the token most recently returned by [`Lexer::next_token`] starts.
+    pub fn token_position(&self) -> Position {
+        self.token_position
+    }
+
+    fn read_token(&mut self) -> Token {
         use crate::token::Token::*;
         self.skip_whitespace();
+        while self.ch == '/' && self.peek_char() == '/' {
+            self.skip_comment();
+            self.skip_whitespace();
+        }
+        self.token_position = Position {
+            line: self.line,
+            column: self.column,
+        };
 
         let token = match self.ch {
             '=' => {
                 if self.peek_char() == '=' {
                     self.read_char();
                     Eq
+                } else if self.peek_char() == '>' {
+                    self.read_char();
+                    FatArrow
                 } else {
                     Assign
                 }
@@ -62,25 +148,71 @@ impl<'a> Lexer<'a> {
                 }
             }
             '*' => Asterisk,
-            '<' => LessThan,
-            '>' => GreaterThan,
+            '&' if self.peek_char() == '&' => {
+                self.read_char();
+                And
+            }
+            '|' if self.peek_char() == '|' => {
+                self.read_char();
+                Or
+            }
+            '<' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    LessEq
+                } else {
+                    LessThan
+                }
+            }
+            '>' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    GreaterEq
+                } else {
+                    GreaterThan
+                }
+            }
             '/' => Slash,
+            '%' => Percent,
             ',' => Comma,
+            ':' => Colon,
             ';' => Semicolon,
-            '(' => Lparen,
-            ')' => Rparen,
+            '\n' => {
+                while self.peek_char().is_whitespace() {
+                    self.read_char();
+                }
+                Newline
+            }
+            '(' => {
+                self.paren_depth += 1;
+                Lparen
+            }
+            ')' => {
+                self.paren_depth = self.paren_depth.saturating_sub(1);
+                Rparen
+            }
             '{' => Lbrace,
             '}' => Rbrace,
+            '\'' => return self.read_char_literal(),
+            '"' => return self.read_string_literal(),
             '\0' => Eof,
             _ if self.ch.is_alphabetic() || self.ch == '_' => {
                 let ident = self.read_identifier();
                 return lookup_ident(&ident);
             }
             _ if self.ch.is_ascii_digit() => {
-                let literal = self.read_number();
-                return Int(literal);
+                let start_position = self.position;
+                let number = self.read_number();
+                // Only a dot followed by a digit makes a float, so `5.` stays an integer
+                if self.ch == '.' && self.peek_char().is_ascii_digit() {
+                    return self.read_fraction(start_position);
+                }
+                return match number {
+                    (Some(value), _) => Int(value),
+                    (None, literal) => IntOverflow(literal.to_string()),
+                };
             }
-            _ => Illegal,
+            _ => Illegal(self.ch.to_string()),
         };
 
         self.read_char();
@@ -96,21 +228,131 @@ impl<'a> Lexer<'a> {
         self.input[start_position..self.position].to_string()
     }
 
-    fn read_number(&mut self) -> i64 {
+    /// Reads a run of digits, returning its value (`None` if it overflows `i64`)
+    /// together with the literal's source text.
+    fn read_number(&mut self) -> (Option<i64>, &'a str) {
         let start_position = self.position;
         while self.ch.is_ascii_digit() {
             self.read_char();
         }
-        self.input[start_position..self.position]
-            .parse::<i64>()
-            .unwrap_or(0)
+        let literal = &self.input[start_position..self.position];
+        (literal.parse::<i64>().ok(), literal)
+    }
+
+    /// Reads the `.` and digits after a float's integer part, which began at `start_position`.
+    ///
+    /// The scan stops at a second dot, so `1.2.3` lexes as `Float(1.2)` followed by the rest.
+    fn read_fraction(&mut self, start_position: usize) -> Token {
+        self.read_char(); // Consume the dot
+        while self.ch.is_ascii_digit() {
+            self.read_char();
+        }
+        let literal = &self.input[start_position..self.position];
+        // A run of digits around one dot always parses; huge values become infinity
+        Token::Float(literal.parse().unwrap_or(f64::INFINITY))
+    }
+
+    /// Reads a single-quoted char literal such as `'a'` or `'\n'`.
+    ///
+    /// Empty, unterminated or badly escaped literals produce `Token::Illegal`, which
+    /// swallows the rest of the literal up to its closing quote or the end of the line.
+    fn read_char_literal(&mut self) -> Token {
+        let start_position = self.position;
+        self.read_char(); // Consume the opening quote
+
+        let value = match self.ch {
+            '\\' => {
+                self.read_char();
+                match self.ch {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    '0' => '\0',
+                    '\\' => '\\',
+                    '\'' => '\'',
+                    _ => return self.malformed_char_literal(start_position),
+                }
+            }
+            '\'' | '\0' => return self.malformed_char_literal(start_position),
+            c => c,
+        };
+
+        self.read_char();
+        if self.ch != '\'' {
+            return self.malformed_char_literal(start_position);
+        }
+        self.read_char(); // Consume the closing quote
+
+        Token::Char(value)
+    }
+
+    /// Skips past the rest of a malformed char literal so its remains aren't
+    /// lexed as separate tokens.
+    fn malformed_char_literal(&mut self, start_position: usize) -> Token {
+        while !matches!(self.ch, '\'' | '\n' | '\0') {
+            self.read_char();
+        }
+        if self.ch == '\'' {
+            self.read_char(); // Consume the closing quote
+        }
+        self.illegal_since(start_position)
+    }
+
+    /// Reads a double-quoted string literal such as `"hi"` or `"a\tb"`, accepting
+    /// the same escapes as char literals plus `\"`.
+    ///
+    /// Unterminated or badly escaped literals produce `Token::Illegal`.
+    fn read_string_literal(&mut self) -> Token {
+        let start_position = self.position;
+        self.read_char(); // Consume the opening quote
+
+        let mut value = String::new();
+        loop {
+            match self.ch {
+                '"' => break,
+                '\0' => return self.illegal_since(start_position),
+                '\\' => {
+                    self.read_char();
+                    value.push(match self.ch {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        '0' => '\0',
+                        '\\' => '\\',
+                        '"' => '"',
+                        _ => return self.illegal_since(start_position),
+                    });
+                }
+                c => value.push(c),
+            }
+            self.read_char();
+        }
+        self.read_char(); // Consume the closing quote
+
+        Token::String(value)
+    }
+
+    /// An illegal token holding the source read since `start_position`.
+    fn illegal_since(&self, start_position: usize) -> Token {
+        Token::Illegal(self.input[start_position..self.position].to_string())
+    }
+
+    /// Skips a `//` comment up to, but not including, the newline that ends it.
+    fn skip_comment(&mut self) {
+        while self.ch != '\n' && self.ch != '\0' {
+            self.read_char();
+        }
     }
 
     fn skip_whitespace(&mut self) {
-        while self.ch.is_whitespace() {
+        while self.ch.is_whitespace() && !self.at_newline_terminator() {
             self.read_char();
         }
     }
+
+    fn at_newline_terminator(&self) -> bool {
+        self.newline_terminators && self.ch == '\n' && self.paren_depth == 0
+    }
 }
 
 // Implement Iterator trait for functional programming style
@@ -404,3 +646,383 @@ fn test_next_token_statements_and_operators() {
         }
     }
 }
+
+#[rstest]
+fn test_max_tokens_stops_at_budget() {
+    let input = "1 + ".repeat(100_000);
+    let lexer = Lexer::new(&input).with_max_tokens(1_000);
+
+    assert_eq!(lexer.count(), 1_000);
+}
+
+#[rstest]
+fn test_max_tokens_returns_eof_after_budget() {
+    use crate::token::Token::*;
+    let mut lexer = Lexer::new("let x = 5;").with_max_tokens(2);
+
+    let tests = vec![Let, Ident("x".to_string()), Eof, Eof];
+
+    for expected_token in tests {
+        let token = lexer.next_token();
+        assert_eq!(token, expected_token);
+    }
+}
+
+#[rstest]
+#[case("'a'", Token::Char('a'))]
+#[case("' '", Token::Char(' '))]
+#[case("'é'", Token::Char('é'))]
+#[case("'\\n'", Token::Char('\n'))]
+#[case("'\\t'", Token::Char('\t'))]
+#[case("'\\''", Token::Char('\''))]
+#[case("'\\\\'", Token::Char('\\'))]
+#[case("''", Token::Illegal("''".to_string()))]
+#[case("'ab'", Token::Illegal("'ab'".to_string()))]
+#[case("'a", Token::Illegal("'a".to_string()))]
+#[case("'\\q'", Token::Illegal("'\\q'".to_string()))]
+fn test_char_literals(#[case] input: &str, #[case] expected: Token) {
+    let mut lexer = Lexer::new(input);
+    assert_eq!(lexer.next_token(), expected);
+}
+
+#[rstest]
+fn test_char_literal_in_statement() {
+    use crate::token::Token::*;
+    let mut lexer = Lexer::new("let c = 'x' + 1;");
+
+    let tests = vec![
+        Let,
+        Ident("c".to_string()),
+        Assign,
+        Char('x'),
+        Plus,
+        Int(1),
+        Semicolon,
+        Eof,
+    ];
+
+    for expected_token in tests {
+        let token = lexer.next_token();
+        assert_eq!(token, expected_token);
+    }
+}
+
+#[rstest]
+fn test_next_token_type_annotation() {
+    use crate::token::Token::*;
+    let mut lexer = Lexer::new("let x: int = 5;");
+
+    let tests = vec![
+        Let,
+        Ident("x".to_string()),
+        Colon,
+        Ident("int".to_string()),
+        Assign,
+        Int(5),
+        Semicolon,
+        Eof,
+    ];
+
+    for expected_token in tests {
+        let token = lexer.next_token();
+        assert_eq!(token, expected_token);
+    }
+}
+
+#[rstest]
+#[case("x\ny", vec![Token::Ident("x".to_string()), Token::Newline, Token::Ident("y".to_string())])]
+#[case("x\n\n  \n y", vec![Token::Ident("x".to_string()), Token::Newline, Token::Ident("y".to_string())])]
+#[case("(x\n)\n", vec![Token::Lparen, Token::Ident("x".to_string()), Token::Rparen, Token::Newline])]
+#[case("{\n}", vec![Token::Lbrace, Token::Newline, Token::Rbrace])]
+fn test_newline_terminators(#[case] input: &str, #[case] expected: Vec<Token>) {
+    let tokens: Vec<Token> = Lexer::new(input).with_newline_terminators(true).collect();
+    assert_eq!(tokens, expected);
+}
+
+#[rstest]
+fn test_newlines_skipped_by_default() {
+    let tokens: Vec<Token> = Lexer::new("x\ny").collect();
+    assert_eq!(
+        tokens,
+        vec![Token::Ident("x".to_string()), Token::Ident("y".to_string())]
+    );
+}
+
+#[rstest]
+#[case("9223372036854775807", Token::Int(i64::MAX))]
+#[case("9223372036854775808", Token::IntOverflow("9223372036854775808".to_string()))]
+#[case(
+    "99999999999999999999",
+    Token::IntOverflow("99999999999999999999".to_string())
+)]
+fn test_integer_literal_range(#[case] input: &str, #[case] expected: Token) {
+    let mut lexer = Lexer::new(input);
+    assert_eq!(lexer.next_token(), expected);
+    assert_eq!(lexer.next_token(), Token::Eof);
+}
+
+#[rstest]
+fn test_reset_tokenizes_new_input_from_start() {
+    let mut lexer = Lexer::new("let x = (5").with_max_tokens(8);
+    assert_eq!(lexer.next_token(), Token::Let);
+    assert_eq!(lexer.next_token(), Token::Ident("x".to_string()));
+
+    lexer.reset("fn(y) { y }");
+    let tokens: Vec<Token> = lexer.by_ref().collect();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Function,
+            Token::Lparen,
+            Token::Ident("y".to_string()),
+            Token::Rparen,
+            Token::Lbrace,
+            Token::Ident("y".to_string()),
+            Token::Rbrace,
+        ]
+    );
+}
+
+#[rstest]
+fn test_match_tokens() {
+    use crate::token::Token::*;
+    let mut lexer = Lexer::new("match x { 1 => 2, _ => 3 } == =>");
+
+    let expected = vec![
+        Match,
+        Ident("x".to_string()),
+        Lbrace,
+        Int(1),
+        FatArrow,
+        Int(2),
+        Comma,
+        Ident("_".to_string()),
+        FatArrow,
+        Int(3),
+        Rbrace,
+        Eq,
+        FatArrow,
+        Eof,
+    ];
+
+    for expected_token in expected {
+        assert_eq!(lexer.next_token(), expected_token);
+    }
+}
+
+#[rstest]
+#[case("\"\"", Token::String(String::new()))]
+#[case("\"hello\"", Token::String("hello".to_string()))]
+#[case("\"hello world\"", Token::String("hello world".to_string()))]
+#[case("\"a\\tb\\n\"", Token::String("a\tb\n".to_string()))]
+#[case("\"say \\\"hi\\\"\"", Token::String("say \"hi\"".to_string()))]
+#[case("\"héllo, 'x'\"", Token::String("héllo, 'x'".to_string()))]
+#[case("\"unterminated", Token::Illegal("\"unterminated".to_string()))]
+#[case("\"", Token::Illegal("\"".to_string()))]
+#[case("\"bad \\q\"", Token::Illegal("\"bad \\".to_string()))]
+fn test_string_literals(#[case] input: &str, #[case] expected: Token) {
+    let mut lexer = Lexer::new(input);
+    assert_eq!(lexer.next_token(), expected);
+}
+
+#[rstest]
+fn test_string_literal_in_statement() {
+    use crate::token::Token::*;
+    let mut lexer = Lexer::new("let s = \"a, b\"; \"unterminated");
+
+    let tests = vec![
+        Let,
+        Ident("s".to_string()),
+        Assign,
+        String("a, b".to_string()),
+        Semicolon,
+        Illegal("\"unterminated".to_string()),
+        Eof,
+    ];
+
+    for expected_token in tests {
+        assert_eq!(lexer.next_token(), expected_token);
+    }
+}
+
+#[rstest]
+#[case(Token::String("hi \"x\"\n".to_string()), "\"hi \\\"x\\\"\\n\"", "String(\"hi \\\"x\\\"\\n\")")]
+#[case(Token::String(String::new()), "\"\"", "String(\"\")")]
+fn test_string_token_rendering(#[case] token: Token, #[case] literal: &str, #[case] display: &str) {
+    assert_eq!(token.to_literal(), literal);
+    assert_eq!(token.to_string(), display);
+}
+
+#[rstest]
+#[case("3.14", Token::Float(3.14))]
+#[case("0.5", Token::Float(0.5))]
+#[case("10.0", Token::Float(10.0))]
+#[case("007.25", Token::Float(7.25))]
+#[case("10", Token::Int(10))]
+#[case("99999999999999999999.5", Token::Float(99999999999999999999.5))]
+// `3.14` is source text under test, not an approximation of PI
+#[allow(clippy::approx_constant)]
+fn test_float_literals(#[case] input: &str, #[case] expected: Token) {
+    let mut lexer = Lexer::new(input);
+    assert_eq!(lexer.next_token(), expected);
+    assert_eq!(lexer.next_token(), Token::Eof);
+}
+
+#[rstest]
+#[case("1.2.3", vec![Token::Float(1.2), Token::Illegal(".".to_string()), Token::Int(3)])]
+#[case("5.", vec![Token::Int(5), Token::Illegal(".".to_string())])]
+#[case("5.x", vec![Token::Int(5), Token::Illegal(".".to_string()), Token::Ident("x".to_string())])]
+#[case("1.5+2", vec![Token::Float(1.5), Token::Plus, Token::Int(2)])]
+fn test_float_literal_boundaries(#[case] input: &str, #[case] expected: Vec<Token>) {
+    let tokens: Vec<Token> = Lexer::new(input).collect();
+    assert_eq!(tokens, expected);
+}
+
+#[rstest]
+#[case("let x = 5; // comment", "let x = 5;")]
+#[case("let x = 5; // comment\n", "let x = 5;")]
+#[case("// leading\n// another\nlet x = 5;", "let x = 5;")]
+#[case("let x = 5;//", "let x = 5;")]
+#[case("let x = 6 / 2; // half of 6 / 1", "let x = 6 / 2;")]
+#[case("let x = '/'; // slash", "let x = '/';")]
+#[case("//", "")]
+fn test_comments_are_discarded(#[case] input: &str, #[case] equivalent: &str) {
+    let tokens: Vec<Token> = Lexer::new(input).collect();
+    let expected: Vec<Token> = Lexer::new(equivalent).collect();
+    assert_eq!(tokens, expected);
+}
+
+#[rstest]
+fn test_comment_keeps_newline_terminator() {
+    use crate::token::Token::*;
+    let mut lexer = Lexer::new("let x = 5 // five\nx").with_newline_terminators(true);
+
+    let tests = vec![
+        Let,
+        Ident("x".to_string()),
+        Assign,
+        Int(5),
+        Newline,
+        Ident("x".to_string()),
+        Eof,
+    ];
+
+    for expected_token in tests {
+        assert_eq!(lexer.next_token(), expected_token);
+    }
+}
+
+#[rstest]
+fn test_token_positions() {
+    let input = "let x = 5;\n// note\n  let total = x +\n\tfoo('é', 10);";
+    let mut lexer = Lexer::new(input);
+
+    let positioned: Vec<(Token, Position)> = std::iter::from_fn(|| {
+        let (token, position) = lexer.next_positioned();
+        (token != Token::Eof).then_some((token, position))
+    })
+    .collect();
+
+    let at = |line, column| Position { line, column };
+    assert_eq!(positioned[0], (Token::Let, at(1, 1)));
+    assert_eq!(positioned[3], (Token::Int(5), at(1, 9)));
+    assert_eq!(positioned[5], (Token::Let, at(3, 3)));
+    assert_eq!(positioned[6], (Token::Ident("total".to_string()), at(3, 7)));
+    assert_eq!(positioned[9], (Token::Plus, at(3, 17)));
+    assert_eq!(positioned[10], (Token::Ident("foo".to_string()), at(4, 2)));
+    assert_eq!(positioned[12], (Token::Char('é'), at(4, 6)));
+    assert_eq!(positioned[14], (Token::Int(10), at(4, 11)));
+}
+
+#[rstest]
+fn test_reset_restarts_positions() {
+    let mut lexer = Lexer::new("\n\nx");
+    assert_eq!(lexer.next_positioned().1, Position { line: 3, column: 1 });
+
+    lexer.reset("  y");
+    assert_eq!(
+        lexer.next_positioned(),
+        (
+            Token::Ident("y".to_string()),
+            Position { line: 1, column: 3 }
+        )
+    );
+}
+
+#[rstest]
+fn test_comparison_tokens() {
+    use crate::token::Token::*;
+    let tokens: Vec<Token> = Lexer::new("a <= b >= c < d > e <=> =<").collect();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Ident("a".to_string()),
+            LessEq,
+            Ident("b".to_string()),
+            GreaterEq,
+            Ident("c".to_string()),
+            LessThan,
+            Ident("d".to_string()),
+            GreaterThan,
+            Ident("e".to_string()),
+            LessEq,
+            GreaterThan,
+            Assign,
+            LessThan,
+        ]
+    );
+}
+
+#[rstest]
+fn test_logical_tokens() {
+    use crate::token::Token::*;
+    let tokens: Vec<Token> = Lexer::new("a && b || c & d | e").collect();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Ident("a".to_string()),
+            And,
+            Ident("b".to_string()),
+            Or,
+            Ident("c".to_string()),
+            Illegal("&".to_string()),
+            Ident("d".to_string()),
+            Illegal("|".to_string()),
+            Ident("e".to_string()),
+        ]
+    );
+}
+
+#[rstest]
+fn test_percent_token() {
+    use crate::token::Token::*;
+    let tokens: Vec<Token> = Lexer::new("10 % 3").collect();
+
+    assert_eq!(tokens, vec![Int(10), Percent, Int(3)]);
+}
+
+#[rstest]
+fn test_malformed_char_literal_is_one_token() {
+    use crate::token::Token::*;
+    let tokens: Vec<Token> = Lexer::new("let c = 'ab'; '\\q' + 1; 'abc\nc").collect();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Let,
+            Ident("c".to_string()),
+            Assign,
+            Illegal("'ab'".to_string()),
+            Semicolon,
+            Illegal("'\\q'".to_string()),
+            Plus,
+            Int(1),
+            Semicolon,
+            Illegal("'abc".to_string()),
+            Ident("c".to_string()),
+        ]
+    );
+}
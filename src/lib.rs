@@ -1,7 +1,16 @@
 pub mod ast;
+pub mod builtins;
+pub mod cli;
+pub mod corpus;
 pub mod environment;
 pub mod evaluator;
 pub mod lexer;
 pub mod object;
 pub mod parser;
+pub mod repl;
+#[cfg(test)]
+mod test_support;
+pub mod timing;
 pub mod token;
+
+pub use ast::{Precedence, precedence_of};
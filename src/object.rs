@@ -1,18 +1,87 @@
-use crate::{ast::Node, environment::Env};
+#[cfg(test)]
+use crate::environment::Environment;
+use crate::{ast::Node, environment::Env, evaluator::Evaluator, token::char_literal};
 use std::fmt::Display;
 
-#[derive(Debug, PartialEq, Clone)]
+use rstest::rstest;
+
+/// Builtins get the running evaluator for its options and output sink.
+pub type BuiltinFunction = fn(Vec<Object>, &Evaluator) -> Object;
+
+/// A function implemented in Rust and exposed to scripts by name.
+#[derive(Debug, Clone, Copy)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub func: BuiltinFunction,
+    /// Receive error arguments instead of having the call short-circuit on them.
+    pub accepts_errors: bool,
+    /// Number of arguments taken, or `None` if it varies.
+    pub arity: Option<usize>,
+}
+
+// Function pointers have no meaningful identity, so builtins compare by name.
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+/// How a result should be presented, e.g. so the REPL can highlight errors.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Category {
+    Value,
+    Error,
+    Null,
+}
+
+#[derive(Debug, Clone)]
 pub enum Object {
     Integer(i64),
+    Char(char),
     Boolean(bool),
     Null,
+    /// A value travelling out of a `return`; function calls and the program unwrap it,
+    /// so it never reaches user code and compares unequal to the bare value on purpose.
     ReturnValue(Box<Object>),
     Error(String),
     Function {
+        /// The name of the `let` binding the function was created for, if any.
+        name: Option<String>,
         parameters: Vec<Node>,
         body: Option<Box<Node>>,
         env: Env,
     },
+    Builtin(Builtin),
+}
+
+// Functions compare by their parameters and body only. The binding name is
+// cosmetic, and the captured environment can be large or refer back to the
+// function itself, so two closures over different scopes are still equal.
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Object::Integer(l), Object::Integer(r)) => l == r,
+            (Object::Char(l), Object::Char(r)) => l == r,
+            (Object::Boolean(l), Object::Boolean(r)) => l == r,
+            (Object::Null, Object::Null) => true,
+            (Object::ReturnValue(l), Object::ReturnValue(r)) => l == r,
+            (Object::Error(l), Object::Error(r)) => l == r,
+            (
+                Object::Function {
+                    parameters: l_parameters,
+                    body: l_body,
+                    ..
+                },
+                Object::Function {
+                    parameters: r_parameters,
+                    body: r_body,
+                    ..
+                },
+            ) => l_parameters == r_parameters && l_body == r_body,
+            (Object::Builtin(l), Object::Builtin(r)) => l == r,
+            _ => false,
+        }
+    }
 }
 
 impl Display for Object {
@@ -25,6 +94,7 @@ impl Object {
     pub fn inspect(&self) -> String {
         match self {
             Object::Integer(value) => value.to_string(),
+            Object::Char(value) => char_literal(*value),
             Object::Boolean(value) => value.to_string(),
             Object::Null => "null".to_string(),
             Object::ReturnValue(value) => value.as_ref().inspect(),
@@ -38,25 +108,252 @@ impl Object {
 
                 let body = body.as_ref().map_or("".to_string(), |b| b.string());
 
-                out.push_str(format!("fn({}) {{\n {} \n}}", params.join(", "), body).as_str());
+                if body.is_empty() {
+                    out.push_str(format!("fn({}) {{}}", params.join(", ")).as_str());
+                } else {
+                    out.push_str(format!("fn({}) {{\n {} \n}}", params.join(", "), body).as_str());
+                }
 
                 out
             }
+            Object::Builtin(builtin) => format!("builtin function {}", builtin.name),
         }
     }
 
     pub fn type_name(&self) -> &'static str {
         match self {
             Object::Integer(_) => "INTEGER",
+            Object::Char(_) => "CHAR",
             Object::Boolean(_) => "BOOLEAN",
             Object::Null => "NULL",
             Object::ReturnValue(_) => "RETURN_VALUE",
             Object::Error(_) => "ERROR",
             Object::Function { .. } => "FUNCTION_OBJ",
+            Object::Builtin(_) => "BUILTIN",
+        }
+    }
+
+    /// How many arguments a function or builtin takes, when that is fixed.
+    pub fn arity(&self) -> Option<usize> {
+        match self {
+            Object::Function { parameters, .. } => Some(parameters.len()),
+            Object::Builtin(builtin) => builtin.arity,
+            _ => None,
+        }
+    }
+
+    /// The name a function was bound with, or a builtin's name.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Object::Function { name, .. } => name.as_deref(),
+            Object::Builtin(builtin) => Some(builtin.name),
+            _ => None,
+        }
+    }
+
+    /// Renders a data value as JSON.
+    ///
+    /// Functions and builtins capture environments or native code that can't be
+    /// written out meaningfully, so they (and error values) are reported instead.
+    pub fn to_serializable(&self) -> Result<String, String> {
+        match self {
+            Object::Integer(value) => Ok(value.to_string()),
+            Object::Boolean(value) => Ok(value.to_string()),
+            Object::Null => Ok("null".to_string()),
+            Object::Char(value) => Ok(json_string(*value)),
+            Object::ReturnValue(value) => value.to_serializable(),
+            Object::Error(message) => Err(format!("cannot serialize an error: {}", message)),
+            Object::Function { .. } | Object::Builtin(_) => Err(format!(
+                "cannot serialize {} value {}",
+                self.type_name(),
+                self.name().unwrap_or("<anonymous>")
+            )),
+        }
+    }
+
+    /// Which kind of result this is for display purposes; a return value takes
+    /// the category of the value it carries.
+    pub fn display_category(&self) -> Category {
+        match self {
+            Object::Error(_) => Category::Error,
+            Object::Null => Category::Null,
+            Object::ReturnValue(value) => value.display_category(),
+            _ => Category::Value,
         }
     }
 
     pub fn is_error(&self) -> bool {
         matches!(self, Object::Error(_))
     }
+
+    /// Turns an error object into `Err(message)` and any other value into `Ok`,
+    /// so embedders can use `?` on evaluation results.
+    pub fn into_result(self) -> Result<Object, String> {
+        match self {
+            Object::Error(message) => Err(message),
+            value => Ok(value),
+        }
+    }
+}
+
+/// Quotes `c` as a one-character JSON string.
+fn json_string(c: char) -> String {
+    let escaped = match c {
+        '"' => "\\\"".to_string(),
+        '\\' => "\\\\".to_string(),
+        '\n' => "\\n".to_string(),
+        '\r' => "\\r".to_string(),
+        '\t' => "\\t".to_string(),
+        c if c.is_control() => format!("\\u{:04x}", u32::from(c)),
+        c => c.to_string(),
+    };
+    format!("\"{}\"", escaped)
+}
+
+impl From<i64> for Object {
+    fn from(value: i64) -> Self {
+        Object::Integer(value)
+    }
+}
+
+impl From<bool> for Object {
+    fn from(value: bool) -> Self {
+        Object::Boolean(value)
+    }
+}
+
+impl From<char> for Object {
+    fn from(value: char) -> Self {
+        Object::Char(value)
+    }
+}
+
+/// Extracting a native value hands back the original object when its type doesn't match.
+impl TryFrom<Object> for i64 {
+    type Error = Object;
+
+    fn try_from(obj: Object) -> Result<Self, Self::Error> {
+        match obj {
+            Object::Integer(value) => Ok(value),
+            other => Err(other),
+        }
+    }
+}
+
+impl TryFrom<Object> for bool {
+    type Error = Object;
+
+    fn try_from(obj: Object) -> Result<Self, Self::Error> {
+        match obj {
+            Object::Boolean(value) => Ok(value),
+            other => Err(other),
+        }
+    }
+}
+
+impl TryFrom<Object> for char {
+    type Error = Object;
+
+    fn try_from(obj: Object) -> Result<Self, Self::Error> {
+        match obj {
+            Object::Char(value) => Ok(value),
+            other => Err(other),
+        }
+    }
+}
+
+#[rstest]
+fn test_native_round_trips() {
+    assert_eq!(i64::try_from(Object::from(42)), Ok(42));
+    assert_eq!(bool::try_from(Object::from(true)), Ok(true));
+    assert_eq!(char::try_from(Object::from('r')), Ok('r'));
+}
+
+#[rstest]
+#[case(Object::Boolean(true))]
+#[case(Object::Char('1'))]
+#[case(Object::Null)]
+fn test_try_from_wrong_type_returns_object(#[case] obj: Object) {
+    assert_eq!(i64::try_from(obj.clone()), Err(obj));
+}
+
+#[rstest]
+#[case(Object::Integer(-7), "-7")]
+#[case(Object::Boolean(false), "false")]
+#[case(Object::Null, "null")]
+#[case(Object::Char('a'), "\"a\"")]
+#[case(Object::Char('"'), "\"\\\"\"")]
+#[case(Object::Char('\n'), "\"\\n\"")]
+#[case(Object::Char('\0'), "\"\\u0000\"")]
+#[case(Object::ReturnValue(Box::new(Object::Integer(1))), "1")]
+fn test_to_serializable(#[case] obj: Object, #[case] expected: &str) {
+    assert_eq!(obj.to_serializable(), Ok(expected.to_string()));
+}
+
+#[rstest]
+#[case(Object::Integer(3), Ok(Object::Integer(3)))]
+#[case(Object::Null, Ok(Object::Null))]
+#[case(
+    Object::Error("division by zero".to_string()),
+    Err("division by zero".to_string())
+)]
+fn test_into_result(#[case] object: Object, #[case] expected: Result<Object, String>) {
+    assert_eq!(object.into_result(), expected);
+}
+
+#[rstest]
+#[case(Object::Integer(1), Category::Value)]
+#[case(Object::Char('a'), Category::Value)]
+#[case(Object::Boolean(false), Category::Value)]
+#[case(Object::Null, Category::Null)]
+#[case(Object::Error("boom".to_string()), Category::Error)]
+#[case(Object::ReturnValue(Box::new(Object::Integer(1))), Category::Value)]
+#[case(Object::ReturnValue(Box::new(Object::Null)), Category::Null)]
+fn test_display_category(#[case] object: Object, #[case] expected: Category) {
+    assert_eq!(object.display_category(), expected);
+}
+
+#[cfg(test)]
+fn identity_function(name: Option<&str>, env: Env) -> Object {
+    let x = Node::Identifier {
+        name: "x".to_string(),
+    };
+    Object::Function {
+        name: name.map(str::to_string),
+        parameters: vec![x.clone()],
+        body: Some(Box::new(Node::Block {
+            statements: vec![Node::ExprStmt {
+                expression: Some(Box::new(x)),
+            }],
+        })),
+        env,
+    }
+}
+
+#[rstest]
+fn test_functions_compare_by_parameters_and_body() {
+    let outer = Environment::new();
+    outer.borrow_mut().set("y", Object::Integer(1)).unwrap();
+
+    assert_eq!(
+        identity_function(Some("f"), Environment::new()),
+        identity_function(Some("g"), outer.clone())
+    );
+
+    // A function stored in its own environment still compares without recursing.
+    let recursive = identity_function(None, outer.clone());
+    outer.borrow_mut().set("self", recursive.clone()).unwrap();
+    assert_eq!(recursive, identity_function(None, Environment::new()));
+
+    let Object::Function { body, env, .. } = identity_function(None, outer) else {
+        unreachable!()
+    };
+    let no_params = Object::Function {
+        name: None,
+        parameters: vec![],
+        body,
+        env,
+    };
+    assert_ne!(no_params, identity_function(None, Environment::new()));
+    assert_ne!(no_params, Object::Null);
 }
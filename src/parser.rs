@@ -5,29 +5,53 @@ use tracing::{debug, error, info};
 use tracing_test::traced_test;
 
 use crate::ast::Node;
-use crate::ast::Precedence;
-use crate::{lexer::Lexer, token::Token};
+use crate::ast::Operator;
+use crate::ast::{Precedence, precedence_of};
+use crate::{
+    lexer::Lexer,
+    token::{Position, Token},
+};
+
+#[cfg(test)]
+use crate::test_support::{ident, infix, int, let_};
+
+/// Type names accepted in `let name: type = value;` annotations.
+pub const TYPE_ANNOTATIONS: [&str; 7] = ["int", "float", "string", "bool", "char", "array", "fn"];
+
+/// Deepest expression nesting accepted before the parser reports an error
+/// rather than risking a native stack overflow.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
 
 pub struct Parser<'a> {
     lexer: &'a mut Lexer<'a>,
     current_token: Token,
     peek_token: Token,
-    pub errors: Vec<ParseError>,
+    current_position: Position,
+    peek_position: Position,
+    errors: Vec<ParseError>,
+    depth: usize,
+    max_depth: usize,
 }
 
 #[derive(Clone, Debug)]
 pub struct ParseError {
     pub message: String,
     pub token: Token,
+    /// Where `token` starts in the source.
+    pub position: Position,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(lexer: &'a mut Lexer<'a>) -> Self {
         let mut parser = Parser {
             lexer,
-            current_token: Token::Illegal, // Initialize with an illegal token
-            peek_token: Token::Illegal,    // Initialize with an illegal token
+            current_token: Token::Illegal(String::new()), // Initialize with an illegal token
+            peek_token: Token::Illegal(String::new()),    // Initialize with an illegal token
+            current_position: Position::default(),
+            peek_position: Position::default(),
             errors: Vec::new(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
         };
 
         parser.next_token(); // Load the first token
@@ -36,14 +60,59 @@ impl<'a> Parser<'a> {
         parser
     }
 
+    /// Overrides how deeply expressions may nest (defaults to [`DEFAULT_MAX_DEPTH`]).
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Points the parser (and its lexer) at `input`, discarding any tokens,
+    /// errors and nesting state left over from the previous input.
+    pub fn reset(&mut self, input: &'a str) {
+        self.lexer.reset(input);
+        self.current_token = Token::Illegal(String::new());
+        self.peek_token = Token::Illegal(String::new());
+        self.current_position = Position::default();
+        self.peek_position = Position::default();
+        self.errors.clear();
+        self.depth = 0;
+
+        self.next_token(); // Load the first token
+        self.next_token(); // Load the second token
+    }
+
+    /// Errors collected while parsing so far.
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    /// Removes and returns the collected errors, leaving the parser with none.
+    pub fn take_errors(&mut self) -> Vec<ParseError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Parses the whole program, returning every collected error if any occurred.
+    pub fn parse(&mut self) -> Result<Node, Vec<ParseError>> {
+        let program = self.parse_program();
+        if self.errors.is_empty() {
+            Ok(program)
+        } else {
+            Err(self.take_errors())
+        }
+    }
+
     pub fn parse_program(&mut self) -> Node {
         info!("BEGIN parse_program");
         let mut statements = vec![];
 
         while self.current_token != Token::Eof {
+            if self.current_token == Token::Newline {
+                self.next_token(); // Blank lines separate nothing
+                continue;
+            }
             match self.parse_statement() {
-                Ok(statement) => {
-                    statements.push(*statement);
+                Ok(parsed) => {
+                    statements.extend(parsed);
                 }
                 Err(e) => {
                     self.errors.push(e); // Collect errors
@@ -61,27 +130,53 @@ impl<'a> Parser<'a> {
 
         let statement = Node::ExprStmt { expression };
 
-        if self.peek_token == Token::Semicolon {
-            self.next_token(); // Consume the semicolon
-        }
+        self.consume_terminator();
 
         info!("END parse_expression_statement");
         Ok(Box::new(statement))
     }
 
     fn parse_expression(&mut self, precedence: Precedence) -> Option<Box<Node>> {
+        if self.depth >= self.max_depth {
+            self.errors.push(ParseError {
+                message: "expression nesting too deep".to_string(),
+                token: self.current_token.clone(),
+                position: self.current_position,
+            });
+
+            // The remaining input can't be parsed reliably, so give up on it
+            while self.peek_token != Token::Eof {
+                self.next_token();
+            }
+            return None;
+        }
+
+        self.depth += 1;
+        let expression = self.parse_nested_expression(precedence);
+        self.depth -= 1;
+
+        expression
+    }
+
+    fn parse_nested_expression(&mut self, precedence: Precedence) -> Option<Box<Node>> {
         use crate::token::Token::*;
         info!("BEGIN parse_expression with precedence: {:?}", precedence);
 
         // this is where the book has a hashmap of prefix functions
         let prefix = match self.current_token.clone() {
             Ident(_) => self.parse_identifier(),
-            Int(_) => self.parse_integer_literal(),
+            Int(_) | IntOverflow(_) => self.parse_integer_literal(),
+            Char(_) => self.parse_char_literal(),
             Bang | Minus => self.parse_prefix_expression(),
             True | False => self.parse_boolean_literal(),
             Lparen => self.parse_grouped_expression(),
             If => self.parse_if_expression(),
             Function => self.parse_function_literal(),
+            Match => self.parse_match_expression(),
+            Return => self.reject_return_expression(),
+            Illegal(text) => self.reject_illegal(&text),
+            Float(_) => self.reject_unsupported_literal("float"),
+            String(_) => self.reject_unsupported_literal("string"),
             _ => None,
         };
 
@@ -90,18 +185,38 @@ impl<'a> Parser<'a> {
         let mut left_expression = prefix;
 
         while self.peek_token.clone() != Token::Semicolon
-            && precedence < self.get_precedence(&self.peek_token.clone())
+            && precedence < precedence_of(&self.peek_token)
         {
-            // this is where the book has a hashmap of infix functions
+            // this is where the book has a hashmap of infix functions. There's
+            // deliberately no catch-all arm, so a new token must be classified here
             left_expression = match self.peek_token.clone() {
                 Lparen => self.parse_call_expression(left_expression),
-                Plus | Minus | Slash | Asterisk | Eq | NotEq | LessThan | GreaterThan => {
+                Assign => {
+                    self.next_token(); // move past the assignment target
+                    self.parse_assign_expression(left_expression)
+                }
+                Plus | Minus | Slash | Percent | Asterisk | Eq | NotEq | LessThan | GreaterThan
+                | LessEq | GreaterEq | And | Or => {
                     self.next_token(); // move past the infix operator
                     self.parse_infix_expression(left_expression)
                 }
-                _ => {
+                operator @ (Illegal(_) | Eof | Ident(_) | Int(_) | IntOverflow(_) | Float(_)
+                | Char(_) | String(_) | FatArrow | Bang | Comma | Colon | Semicolon
+                | Newline | Rparen | Lbrace | Rbrace | Function | Let | True
+                | False | If | Else | Return | Match) => {
+                    // Only tokens with a precedence reach this loop, so one without an
+                    // infix handler here is a parser bug rather than a user mistake
+                    error!(
+                        "operator {} has a precedence but no infix handler",
+                        operator
+                    );
+                    self.errors.push(ParseError {
+                        message: format!("operator {} not wired into parser", operator),
+                        token: operator,
+                        position: self.peek_position,
+                    });
                     info!("END parse_expression");
-                    return left_expression; // No infix function, return the left expression
+                    return left_expression;
                 }
             };
         }
@@ -114,10 +229,14 @@ impl<'a> Parser<'a> {
     fn parse_infix_expression(&mut self, left: Option<Box<Node>>) -> Option<Box<Node>> {
         info!("BEGIN parse_infix_expression");
         let current_token = self.current_token.clone();
-        let operator = current_token.to_literal();
-        let precedence = self.get_precedence(&current_token);
+        let Some(operator) = Operator::from_token(&current_token) else {
+            info!("END parse_infix_expression - not an operator");
+            return left;
+        };
+        let precedence = precedence_of(&current_token);
 
         self.next_token(); // Move past the operator
+        self.skip_newlines(); // An operator at the end of a line continues the expression
 
         let right = self.parse_expression(precedence);
 
@@ -125,6 +244,7 @@ impl<'a> Parser<'a> {
             self.errors.push(ParseError {
                 message: "Expected expression after infix operator".to_string(),
                 token: self.current_token.clone(),
+                position: self.current_position,
             });
 
             return left;
@@ -147,8 +267,46 @@ impl<'a> Parser<'a> {
         // ))))
     }
 
+    fn parse_assign_expression(&mut self, target: Option<Box<Node>>) -> Option<Box<Node>> {
+        info!("BEGIN parse_assign_expression");
+        let is_identifier = matches!(target.as_deref(), Some(Node::Identifier { .. }));
+        if !is_identifier {
+            self.errors.push(ParseError {
+                message: "Invalid assignment target".to_string(),
+                token: self.current_token.clone(),
+                position: self.current_position,
+            });
+        }
+
+        self.next_token(); // Move past the '='
+        self.skip_newlines();
+
+        // Parsing the value at the lowest precedence makes assignment right-associative:
+        // `a = b = 1` groups as `a = (b = 1)`
+        let value = self.parse_expression(Precedence::Lowest);
+
+        if value.is_none() {
+            self.errors.push(ParseError {
+                message: "Expected expression after '='".to_string(),
+                token: self.current_token.clone(),
+                position: self.current_position,
+            });
+        }
+
+        if !is_identifier || value.is_none() {
+            return target;
+        }
+
+        info!("END parse_assign_expression");
+        Some(Box::new(Node::Assign {
+            name: target,
+            value,
+        }))
+    }
+
     fn parse_call_expression(&mut self, function: Option<Box<Node>>) -> Option<Box<Node>> {
         info!("BEGIN parse_call_expression");
+        self.next_token(); // Move onto the l paren
         let arguments = self.parse_call_arguments();
 
         info!("END parse_call_expression");
@@ -158,6 +316,7 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    /// Parses a call's argument list, starting on its `(` and leaving the `)` as the current token.
     fn parse_call_arguments(&mut self) -> Vec<Node> {
         info!("BEGIN parse_call_arguments");
         let mut args = Vec::new();
@@ -165,11 +324,11 @@ impl<'a> Parser<'a> {
         if self.peek_token == Token::Rparen {
             self.next_token();
 
-            info!("END parse_call_arguments - pt == rparen");
+            info!("END parse_call_arguments - no arguments");
             return args;
         }
 
-        self.next_token();
+        self.next_token(); // Move past the l paren
 
         if let Some(e) = self.parse_expression(Precedence::Lowest) {
             args.push(*e);
@@ -185,10 +344,14 @@ impl<'a> Parser<'a> {
 
         if self.peek_token != Token::Rparen {
             info!("END parse_call_arguments - pt != rparen");
+            self.errors.push(ParseError {
+                message: "Expected ')' after call arguments".to_string(),
+                token: self.peek_token.clone(),
+                position: self.peek_position,
+            });
             return args;
-        } else {
-            self.next_token();
         }
+        self.next_token();
 
         info!("END parse_call_arguments");
         args
@@ -218,7 +381,8 @@ impl<'a> Parser<'a> {
 
         let consequence = self.parse_block_statement();
 
-        let alternative = if self.peek_token == Token::Else {
+        let alternative = if self.peek_past_newlines_is(&Token::Else) {
+            self.skip_peek_newlines();
             self.next_token(); // consume r brace
 
             match self.peek_token {
@@ -243,39 +407,146 @@ impl<'a> Parser<'a> {
         //     ))))
     }
 
+    /// Parses `match subject { pattern => value, ..., _ => default }`, leaving the `}`
+    /// as the current token.
+    fn parse_match_expression(&mut self) -> Option<Box<Node>> {
+        info!("BEGIN parse_match_expression");
+        self.next_token(); // Move past 'match'
+        let subject = self.parse_expression(Precedence::Lowest);
+
+        if self.peek_token != Token::Lbrace {
+            info!("END parse_match_expression - did not find l brace");
+            self.errors.push(ParseError {
+                message: "Expected '{' after match subject".to_string(),
+                token: self.peek_token.clone(),
+                position: self.peek_position,
+            });
+            return None;
+        }
+
+        self.next_token(); // Move onto the l brace
+        self.next_token(); // Move past it
+        self.skip_newlines();
+
+        let mut arms = Vec::new();
+        let mut default = None;
+        while self.current_token != Token::Rbrace {
+            if default.is_some() {
+                info!("END parse_match_expression - arm after default");
+                self.errors.push(ParseError {
+                    message: "Unreachable match arm after '_'".to_string(),
+                    token: self.current_token.clone(),
+                    position: self.current_position,
+                });
+                return None;
+            }
+
+            let pattern = if self.current_token == Token::Ident("_".to_string()) {
+                None
+            } else {
+                match self.parse_expression(Precedence::Lowest) {
+                    Some(pattern) => Some(pattern),
+                    None => {
+                        info!("END parse_match_expression - no pattern");
+                        self.errors.push(ParseError {
+                            message: "Expected pattern in match arm".to_string(),
+                            token: self.current_token.clone(),
+                            position: self.current_position,
+                        });
+                        return None;
+                    }
+                }
+            };
+
+            if self.peek_token != Token::FatArrow {
+                info!("END parse_match_expression - did not find fat arrow");
+                self.errors.push(ParseError {
+                    message: "Expected '=>' after match pattern".to_string(),
+                    token: self.peek_token.clone(),
+                    position: self.peek_position,
+                });
+                return None;
+            }
+
+            self.next_token(); // Move onto the '=>'
+            self.next_token(); // Move past it
+            self.skip_newlines();
+
+            let Some(value) = self.parse_expression(Precedence::Lowest) else {
+                info!("END parse_match_expression - no arm value");
+                self.errors.push(ParseError {
+                    message: "Expected expression after '=>'".to_string(),
+                    token: self.current_token.clone(),
+                    position: self.current_position,
+                });
+                return None;
+            };
+
+            match pattern {
+                Some(pattern) => arms.push((*pattern, *value)),
+                None => default = Some(value),
+            }
+
+            self.next_token(); // Move past the arm's value
+            self.skip_newlines();
+            if self.current_token == Token::Comma {
+                self.next_token();
+                self.skip_newlines();
+            } else if self.current_token != Token::Rbrace {
+                info!("END parse_match_expression - arm not terminated");
+                self.errors.push(ParseError {
+                    message: "Expected ',' or '}' after match arm".to_string(),
+                    token: self.current_token.clone(),
+                    position: self.current_position,
+                });
+                return None;
+            }
+        }
+
+        info!("END parse_match_expression");
+        Some(Box::new(Node::Match {
+            subject,
+            arms,
+            default,
+        }))
+    }
+
     fn parse_function_literal(&mut self) -> Option<Box<Node>> {
         info!("BEGIN parse_function_literal");
-        self.next_token();
-
-        if self.current_token != Token::Lparen {
+        if self.peek_token != Token::Lparen {
             info!("END parse_function_literal - did not find l paren");
+            self.errors.push(ParseError {
+                message: "Expected '(' after 'fn'".to_string(),
+                token: self.peek_token.clone(),
+                position: self.peek_position,
+            });
             return None;
         }
 
+        self.next_token(); // Move onto the l paren
+
         let parameters: Vec<Node> = self.parse_fn_params();
+        self.skip_peek_newlines(); // The body may start on the next line
 
         if self.peek_token != Token::Lbrace {
             info!("END parse_function_literal - did not find l brace");
-            let body = Some(Box::new(Node::Block { statements: vec![] }));
-            return Some(Box::new(Node::Function {
-                // token,
-                parameters,
-                body,
-            }));
+            self.errors.push(ParseError {
+                message: "Expected '{' after function parameters".to_string(),
+                token: self.peek_token.clone(),
+                position: self.peek_position,
+            });
+            return None;
         }
 
-        self.next_token(); // consume l brace
+        self.next_token(); // Move onto the l brace
 
         let body = self.parse_block_statement();
 
         info!("END parse_function_literal");
-        Some(Box::new(Node::Function {
-            // token,
-            parameters,
-            body,
-        }))
+        Some(Box::new(Node::Function { parameters, body }))
     }
 
+    /// Parses a parenthesised parameter list, leaving the closing paren as the current token.
     fn parse_fn_params(&mut self) -> Vec<Node> {
         info!("BEGIN parse_fn_params");
 
@@ -284,7 +555,6 @@ impl<'a> Parser<'a> {
         let mut params = Vec::new();
 
         if self.current_token == Token::Rparen {
-            self.next_token(); // consume r paren
             info!("END parse_fn_params - no params");
             return params;
         }
@@ -320,8 +590,12 @@ impl<'a> Parser<'a> {
         self.next_token(); // Consume the opening brace
 
         while self.current_token != Token::Rbrace && self.current_token != Token::Eof {
+            if self.current_token == Token::Newline {
+                self.next_token();
+                continue;
+            }
             match self.parse_statement() {
-                Ok(stmt) => statements.push(*stmt),
+                Ok(parsed) => statements.extend(parsed),
                 Err(e) => {
                     error!("Error parsing block statement: {:?}", e);
                     self.errors.push(e);
@@ -334,55 +608,27 @@ impl<'a> Parser<'a> {
         Some(Box::new(Node::Block { statements }))
     }
 
-    fn get_precedence(&self, token: &Token) -> Precedence {
-        use crate::token::Token::*;
-        match token {
-            Lparen => Precedence::Call,
-            Eq | NotEq => Precedence::Equals,
-            LessThan | GreaterThan => Precedence::LessGreater,
-            Plus | Minus => Precedence::Sum,
-            Asterisk | Slash => Precedence::Product,
-            _ => Precedence::Lowest,
-        }
-    }
-
     fn parse_prefix_expression(&mut self) -> Option<Box<Node>> {
         info!("BEGIN parse_prefix_expression");
-        match self.current_token {
-            Token::Bang | Token::Minus => {
-                let current_token = self.current_token.clone();
-                let operator = current_token.to_literal();
-                self.next_token();
-                let right = self.parse_expression(Precedence::Prefix);
-                if right.is_none() {
-                    self.errors.push(ParseError {
-                        message: "Expected expression after prefix operator".to_string(),
-                        token: self.current_token.clone(),
-                    });
-                }
-                info!("END parse_prefix_expression");
-                Some(Box::new(Node::Prefix {
-                    // current_token,
-                    operator,
-                    right,
-                }))
-            }
-            Token::Lbrace => {
-                let current_token = self.current_token.clone();
-                let operator = current_token.to_literal();
-                let right = self.parse_grouped_expression();
-
-                Some(Box::new(Node::Prefix {
-                    // current_token,
-                    operator,
-                    right,
-                }))
-            }
-            _ => {
-                info!("END parse_prefix_expression");
-                None
-            }
+        let Some(operator) = Operator::from_token(&self.current_token) else {
+            info!("END parse_prefix_expression");
+            return None;
+        };
+        self.next_token();
+        let right = self.parse_expression(Precedence::Prefix);
+        if right.is_none() {
+            self.errors.push(ParseError {
+                message: "Expected expression after prefix operator".to_string(),
+                token: self.current_token.clone(),
+                position: self.current_position,
+            });
         }
+        info!("END parse_prefix_expression");
+        Some(Box::new(Node::Prefix {
+            // current_token,
+            operator,
+            right,
+        }))
     }
 
     fn parse_grouped_expression(&mut self) -> Option<Box<Node>> {
@@ -402,6 +648,48 @@ impl<'a> Parser<'a> {
         expression
     }
 
+    /// Reports source the lexer couldn't tokenize, at the position it starts.
+    fn reject_illegal(&mut self, text: &str) -> Option<Box<Node>> {
+        let message = if text.starts_with(['\'', '"']) {
+            format!("malformed literal {}", text)
+        } else {
+            format!("unexpected character '{}'", text)
+        };
+        self.errors.push(ParseError {
+            message,
+            token: self.current_token.clone(),
+            position: self.current_position,
+        });
+        None
+    }
+
+    /// Reports a literal the lexer recognizes but the language can't evaluate yet.
+    fn reject_unsupported_literal(&mut self, kind: &str) -> Option<Box<Node>> {
+        self.errors.push(ParseError {
+            message: format!("{} literals are not supported yet", kind),
+            token: self.current_token.clone(),
+            position: self.current_position,
+        });
+        None
+    }
+
+    fn reject_return_expression(&mut self) -> Option<Box<Node>> {
+        info!("BEGIN reject_return_expression");
+        self.errors.push(ParseError {
+            message: "'return' is a statement and cannot be used as an expression".to_string(),
+            token: self.current_token.clone(),
+            position: self.current_position,
+        });
+
+        // Skip the would-be return value so it isn't reported again as a statement
+        if !self.peek_ends_statement() {
+            self.next_token();
+            self.parse_expression(Precedence::Lowest);
+        }
+        info!("END reject_return_expression");
+        None
+    }
+
     fn parse_identifier(&mut self) -> Option<Box<Node>> {
         info!("BEGIN parse_identifier");
         if let Token::Ident(ref ident) = self.current_token {
@@ -420,6 +708,14 @@ impl<'a> Parser<'a> {
         if let Token::Int(value) = self.current_token {
             info!("END parse_integer_literal");
             Some(Box::new(Node::IntegerLiteral { value }))
+        } else if let Token::IntOverflow(literal) = &self.current_token {
+            info!("END parse_integer_literal - out of range");
+            self.errors.push(ParseError {
+                message: format!("integer literal {} is out of range for i64", literal),
+                token: self.current_token.clone(),
+                position: self.current_position,
+            });
+            None
         } else {
             info!(
                 "END parse_integer_literal - not int, {:?}",
@@ -429,6 +725,17 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn parse_char_literal(&mut self) -> Option<Box<Node>> {
+        info!("BEGIN parse_char_literal");
+        if let Token::Char(value) = self.current_token {
+            info!("END parse_char_literal");
+            Some(Box::new(Node::CharLiteral { value }))
+        } else {
+            info!("END parse_char_literal - not char");
+            None
+        }
+    }
+
     // fn parse_boolean_literal(&mut self) -> Option<NodeType> {
     fn parse_boolean_literal(&mut self) -> Option<Box<Node>> {
         info!("BEGIN parse_boolean_literal");
@@ -446,41 +753,105 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_statement(&mut self) -> Result<Box<Node>, ParseError> {
+    /// Parses one source statement, which may stand for several nodes:
+    /// `let a = 1, b = 2;` becomes one `Node::Let` per binding.
+    fn parse_statement(&mut self) -> Result<Vec<Node>, ParseError> {
         info!("BEGIN parse_statement");
         debug!("Current token: {:?}", self.current_token);
-        let statement = match self.current_token {
+        let statements = match self.current_token {
             Token::Let => self.parse_let_statement(),
-            Token::Return => self.parse_return_statement(),
-            _ => self.parse_expression_statement(),
+            Token::Return => self.parse_return_statement().map(|s| vec![*s]),
+            _ => self.parse_expression_statement().map(|s| vec![*s]),
         };
         info!("END parse_statement");
-        debug!("Parsed statement: {:?}", statement);
-        statement
+        // Only the kinds: formatting the whole tree recurses once per nesting level
+        debug!(
+            "Parsed statements: {:?}",
+            statements
+                .as_ref()
+                .map(|statements| statements.iter().map(Node::kind).collect::<Vec<_>>())
+        );
+        statements
     }
 
-    fn parse_let_statement(&mut self) -> Result<Box<Node>, ParseError> {
+    /// Parses `let a = 1, b = a + 1;` into one `Node::Let` per binding, in order.
+    fn parse_let_statement(&mut self) -> Result<Vec<Node>, ParseError> {
         info!("BEGIN parse_let_statement");
-        self.next_token(); // Move past the 'let' token
+        let mut bindings = Vec::new();
+        let mut separator = "let";
+        loop {
+            self.next_token(); // Move past the 'let' or ','
+            self.skip_newlines();
+            bindings.push(self.parse_let_binding(separator)?);
+
+            if self.peek_token != Token::Comma {
+                break;
+            }
+            self.next_token(); // Move onto the ','
+            separator = ",";
+        }
+
+        self.expect_terminator("let")?;
+
+        info!("END parse_let_statement");
+        Ok(bindings)
+    }
+
+    /// Parses `name[: type] = value`, starting on the name and leaving the value's
+    /// last token as the current token.
+    fn parse_let_binding(&mut self, after: &str) -> Result<Node, ParseError> {
         let name = if let Token::Ident(name) = self.current_token.clone() {
             name
         } else {
             info!("END parse_let_statement - not id");
             return Err(ParseError {
-                message: "Expected identifier after 'let'".to_string(),
+                message: format!("Expected identifier after '{}'", after),
                 token: self.current_token.clone(),
+                position: self.current_position,
             });
         };
 
+        let type_annotation = if self.peek_token == Token::Colon {
+            self.next_token(); // Move onto the ':'
+            self.next_token(); // Move onto the type name
+            match self.current_token.clone() {
+                Token::Ident(type_name) if TYPE_ANNOTATIONS.contains(&type_name.as_str()) => {
+                    Some(type_name)
+                }
+                // `fn` lexes as a keyword rather than an identifier
+                Token::Function => Some("fn".to_string()),
+                Token::Ident(type_name) => {
+                    info!("END parse_let_statement - unknown type");
+                    return Err(ParseError {
+                        message: format!("Unknown type annotation '{}'", type_name),
+                        token: self.current_token.clone(),
+                        position: self.current_position,
+                    });
+                }
+                _ => {
+                    info!("END parse_let_statement - no type");
+                    return Err(ParseError {
+                        message: "Expected type name after ':'".to_string(),
+                        token: self.current_token.clone(),
+                        position: self.current_position,
+                    });
+                }
+            }
+        } else {
+            None
+        };
+
         if self.peek_token != Token::Assign {
             info!("END parse_let_statement - no assign");
             return Err(ParseError {
                 message: "Expected '=' after variable name".to_string(),
                 token: self.current_token.clone(),
+                position: self.current_position,
             });
         }
         self.next_token(); //
         self.next_token(); // Move past the identifier
+        self.skip_newlines();
 
         let value = self.parse_expression(Precedence::Lowest);
 
@@ -489,38 +860,106 @@ impl<'a> Parser<'a> {
             self.current_token, self.peek_token
         );
 
-        while self.current_token != Token::Semicolon && self.current_token != Token::Eof {
-            self.next_token(); // Skip tokens until we reach a semicolon or EOF
-        }
-
-        info!("END parse_let_statement");
         let name = Some(Box::new(Node::Identifier { name }));
-        Ok(Box::new(Node::Let {
+        Ok(Node::Let {
             // let_token,
             name,
+            type_annotation,
             value,
-        }))
+        })
     }
 
     fn parse_return_statement(&mut self) -> Result<Box<Node>, ParseError> {
         info!("BEGIN parse_return_statement");
-        self.next_token(); // move past the 'return' token
-
-        let return_value = self.parse_expression(Precedence::Lowest);
+        let return_value = if self.peek_ends_statement() {
+            None // A bare `return`
+        } else {
+            self.next_token(); // move past the 'return' token
+            self.parse_expression(Precedence::Lowest)
+        };
         let return_statement = Node::Return {
             /*return_token,*/ return_value,
         };
-        while self.current_token != Token::Semicolon {
-            self.next_token(); // Skip tokens until we reach a semicolon
-        }
+        self.expect_terminator("return")?;
 
         info!("END parse_return_statement");
         Ok(Box::new(return_statement))
     }
 
+    /// Whether the next token ends a statement: `;`, a newline terminator, a block's `}` or EOF.
+    fn peek_ends_statement(&self) -> bool {
+        matches!(
+            self.peek_token,
+            Token::Semicolon | Token::Newline | Token::Rbrace | Token::Eof
+        )
+    }
+
+    /// Consumes the statement's optional terminator (`;` or a newline terminator).
+    fn consume_terminator(&mut self) {
+        if matches!(self.peek_token, Token::Semicolon | Token::Newline) {
+            self.next_token();
+        }
+    }
+
+    /// Consumes the terminator after a `let` or `return` statement, which may only be
+    /// left out before a line break, a block's `}` or the end of input.
+    ///
+    /// A line break counts even when the lexer doesn't emit newline tokens, since the
+    /// next token then starts on a later line.
+    fn expect_terminator(&mut self, statement: &str) -> Result<(), ParseError> {
+        match self.peek_token {
+            Token::Semicolon | Token::Newline => {
+                self.next_token();
+                Ok(())
+            }
+            // Left for the next statement, which reports it more precisely
+            Token::Rbrace | Token::Eof | Token::Illegal(_) => Ok(()),
+            _ if self.peek_position.line > self.current_position.line => Ok(()),
+            _ => Err(ParseError {
+                message: format!(
+                    "Expected ';' or a line break after '{}' statement",
+                    statement
+                ),
+                token: self.peek_token.clone(),
+                position: self.peek_position,
+            }),
+        }
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.current_token == Token::Newline {
+            self.next_token();
+        }
+    }
+
+    /// Moves past newline terminators until the peek token is something else.
+    fn skip_peek_newlines(&mut self) {
+        while self.peek_token == Token::Newline {
+            self.next_token();
+        }
+    }
+
+    /// Whether `token` is next once any newline terminators are skipped.
+    ///
+    /// Looks ahead on a copy of the lexer, so a newline that turns out to end
+    /// the statement is left in place rather than consumed.
+    fn peek_past_newlines_is(&self, token: &Token) -> bool {
+        if self.peek_token != Token::Newline {
+            return self.peek_token == *token;
+        }
+        let mut lookahead = self.lexer.clone();
+        loop {
+            match lookahead.next_token() {
+                Token::Newline => continue,
+                next => return next == *token,
+            }
+        }
+    }
+
     pub fn next_token(&mut self) {
         self.current_token = self.peek_token.clone();
-        self.peek_token = self.lexer.next_token();
+        self.current_position = self.peek_position;
+        (self.peek_token, self.peek_position) = self.lexer.next_positioned();
 
         debug!("ct: {:?} | pt: {:?}", self.current_token, self.peek_token);
     }
@@ -549,7 +988,7 @@ fn test_let_statements() {
     let program = parser.parse_program();
 
     let errors = parser
-        .errors
+        .errors()
         .iter()
         .filter(|e| !e.message.starts_with("TEMP:"))
         .collect::<Vec<&ParseError>>();
@@ -567,48 +1006,19 @@ fn test_let_statements() {
     assert_eq!(statements.len(), 5);
     assert_eq!(
         statements.next().expect("Expected first statement"),
-        Node::Let {
-            // Token::Let,
-            name: Some(Box::new(Node::Identifier {
-                name: "x".to_string()
-            })),
-            value: Some(Box::new(Node::IntegerLiteral { value: 5 })),
-        }
+        let_!("x", int!(5))
     );
-
     assert_eq!(
         statements.next().expect("Expected second statement"),
-        Node::Let {
-            // Token::Let,
-            name: Some(Box::new(Node::Identifier {
-                name: "y".to_string()
-            })),
-            value: Some(Box::new(Node::IntegerLiteral { value: 10 })),
-        }
+        let_!("y", int!(10))
     );
     assert_eq!(
         statements.next().expect("Expected third statement"),
-        Node::Let {
-            // Token::Let,
-            name: Some(Box::new(Node::Identifier {
-                name: "foobar".to_string()
-            })),
-            value: Some(Box::new(Node::IntegerLiteral { value: 838383 })),
-        }
+        let_!("foobar", int!(838383))
     );
     assert_eq!(
         statements.next().expect("Expected fourth statement"),
-        Node::Let {
-            // Token::Let,
-            name: Some(Box::new(Node::Identifier {
-                name: "foobar".to_string()
-            })),
-            value: Some(Box::new(Node::Infix {
-                left: Some(Box::new(Node::IntegerLiteral { value: 1 })),
-                operator: "+".to_string(),
-                right: Some(Box::new(Node::IntegerLiteral { value: 2 }))
-            })),
-        }
+        let_!("foobar", infix!("+", int!(1), int!(2)))
     );
 }
 
@@ -625,11 +1035,11 @@ fn test_broken_let_statements() {
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    parser.errors.clone().into_iter().for_each(|e| {
+    parser.errors().iter().for_each(|e| {
         eprintln!("Error: {} at token {:?}", e.message, e.token);
     });
 
-    let mut errors = parser.errors.into_iter();
+    let mut errors = parser.take_errors().into_iter();
     let statements = match program {
         Node::Program { statements, .. } => statements,
         _ => panic!("Expected a program node"),
@@ -670,7 +1080,7 @@ fn test_return_statements() {
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     errors.clone().for_each(|e| {
         eprintln!("Error: {} at token {:?}", e.message, e.token);
@@ -700,7 +1110,7 @@ fn test_identifier_expression() {
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     errors.clone().for_each(|e| {
         eprintln!("Error: {} at token {:?}", e.message, e.token);
@@ -731,7 +1141,7 @@ fn test_integer_literal_expression() {
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     errors.clone().for_each(|e| {
         eprintln!("Error: {} at token {:?}", e.message, e.token);
@@ -760,7 +1170,7 @@ fn test_boolean_literal_expression() {
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     errors.clone().for_each(|e| {
         eprintln!("Error: {} at token {:?}", e.message, e.token);
@@ -782,20 +1192,20 @@ fn test_boolean_literal_expression() {
 }
 
 #[rstest]
-#[case("!5;", "!", TestValue::Integer(5))]
-#[case("-1;", "-", TestValue::Integer(1))]
-#[case("!true;", "!", TestValue::Boolean(true))]
-#[case("!false;", "!", TestValue::Boolean(false))]
+#[case("!5;", Operator::Bang, TestValue::Integer(5))]
+#[case("-1;", Operator::Minus, TestValue::Integer(1))]
+#[case("!true;", Operator::Bang, TestValue::Boolean(true))]
+#[case("!false;", Operator::Bang, TestValue::Boolean(false))]
 fn test_parsing_prefix_expression(
     #[case] input: &str,
-    #[case] operator: &str,
+    #[case] operator: Operator,
     #[case] value: TestValue,
 ) {
     let mut lexer = Lexer::new(input);
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     errors.clone().for_each(|e| {
         eprintln!("Error: {} at token {:?}", e.message, e.token);
@@ -813,8 +1223,6 @@ fn test_parsing_prefix_expression(
         TestValue::Boolean(b) => Node::BooleanLiteral { value: b },
     };
 
-    let operator = operator.to_string();
-
     assert_eq!(
         statements.first().expect("Expected at least one statement"),
         &Node::ExprStmt {
@@ -827,43 +1235,79 @@ fn test_parsing_prefix_expression(
 }
 
 #[rstest]
-#[case("5 + 5;", TestValue::Integer(5), "+", TestValue::Integer(5))]
-#[case("5 - 5;", TestValue::Integer(5), "-", TestValue::Integer(5))]
-#[case("5 * 5;", TestValue::Integer(5), "*", TestValue::Integer(5))]
-#[case("5 / 5;", TestValue::Integer(5), "/", TestValue::Integer(5))]
-#[case("5 > 5;", TestValue::Integer(5), ">", TestValue::Integer(5))]
-#[case("5 < 5;", TestValue::Integer(5), "<", TestValue::Integer(5))]
-#[case("5 == 5;", TestValue::Integer(5), "==", TestValue::Integer(5))]
-#[case("5 != 5;", TestValue::Integer(5), "!=", TestValue::Integer(5))]
+#[case("5 + 5;", TestValue::Integer(5), Operator::Plus, TestValue::Integer(5))]
+#[case(
+    "5 - 5;",
+    TestValue::Integer(5),
+    Operator::Minus,
+    TestValue::Integer(5)
+)]
+#[case(
+    "5 * 5;",
+    TestValue::Integer(5),
+    Operator::Asterisk,
+    TestValue::Integer(5)
+)]
+#[case(
+    "5 / 5;",
+    TestValue::Integer(5),
+    Operator::Slash,
+    TestValue::Integer(5)
+)]
+#[case(
+    "5 % 5;",
+    TestValue::Integer(5),
+    Operator::Percent,
+    TestValue::Integer(5)
+)]
+#[case(
+    "5 > 5;",
+    TestValue::Integer(5),
+    Operator::GreaterThan,
+    TestValue::Integer(5)
+)]
+#[case(
+    "5 < 5;",
+    TestValue::Integer(5),
+    Operator::LessThan,
+    TestValue::Integer(5)
+)]
+#[case("5 == 5;", TestValue::Integer(5), Operator::Eq, TestValue::Integer(5))]
+#[case(
+    "5 != 5;",
+    TestValue::Integer(5),
+    Operator::NotEq,
+    TestValue::Integer(5)
+)]
 #[case(
     "true == true;",
     TestValue::Boolean(true),
-    "==",
+    Operator::Eq,
     TestValue::Boolean(true)
 )]
 #[case(
     "true != false;",
     TestValue::Boolean(true),
-    "!=",
+    Operator::NotEq,
     TestValue::Boolean(false)
 )]
 #[case(
     "false == false;",
     TestValue::Boolean(false),
-    "==",
+    Operator::Eq,
     TestValue::Boolean(false)
 )]
 fn test_infix_expression(
     #[case] input: &str,
     #[case] left_value: TestValue,
-    #[case] operator: &str,
+    #[case] operator: Operator,
     #[case] right_value: TestValue,
 ) {
     let mut lexer = Lexer::new(input);
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     // errors.clone().into_iter().for_each(|e| {
     //     eprintln!("Error: {} at token {:?}", e.message, e.token);
@@ -890,7 +1334,7 @@ fn test_infix_expression(
         Some(&Node::ExprStmt {
             expression: Some(Box::new(Node::Infix {
                 left: Some(Box::new(left_value)),
-                operator: operator.to_string(),
+                operator,
                 right: Some(Box::new(right_value)),
             })),
         })
@@ -914,13 +1358,24 @@ fn test_infix_expression(
 #[case("3 + 4; -5 * 5", "(3 + 4)((-5) * 5)")]
 #[case("5 > 4 == 3 < 4", "((5 > 4) == (3 < 4))")]
 #[case("5 < 4 != 3 > 4", "((5 < 4) != (3 > 4))")]
+#[case("1 <= 2 == true", "((1 <= 2) == true)")]
+#[case("a >= b + 1 != false", "((a >= (b + 1)) != false)")]
+#[case("1 <= 2 >= 3", "((1 <= 2) >= 3)")]
+#[case("a + b % c * d", "(a + ((b % c) * d))")]
+#[case("a == b && c != d", "((a == b) && (c != d))")]
+#[case("a || b && c", "(a || (b && c))")]
+#[case("a && b || c && d", "((a && b) || (c && d))")]
+#[case("x = a || b", "(x = (a || b))")]
 #[case("3 + 4 * 5 == 3 * 1 + 4 * 5", "((3 + (4 * 5)) == ((3 * 1) + (4 * 5)))")]
 #[case("1 + (2 + 3) + 4", "((1 + (2 + 3)) + 4)")]
 #[case("(5 + 5) * 2", "((5 + 5) * 2)")]
 #[case("2 / (5 + 5)", "(2 / (5 + 5))")]
 #[case("-(5 + 5)", "(-(5 + 5))")]
 #[case("!(true == true)", "(!(true == true))")]
+#[case("a + add(b * c) + d", "((a + add((b * c))) + d)")]
 #[case("(a + add(b * c)) + d", "((a + add((b * c))) + d)")]
+#[case("-add(a) * b", "((-add(a)) * b)")]
+#[case("!-add(a)", "(!(-add(a)))")]
 #[case(
     "add(a, b, 1, 2 * 3, 4 + 5, add(6, 7 * 8))",
     "add(a, b, 1, (2 * 3), (4 + 5), add(6, (7 * 8)))"
@@ -931,7 +1386,7 @@ fn test_operator_precedence_parsing(#[case] input: &str, #[case] expected_output
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     errors.clone().for_each(|e| {
         eprintln!("Error: {} at token {:?}", e.message, e.token);
@@ -953,7 +1408,7 @@ fn test_if_expresssion() {
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     // errors.clone().into_iter().for_each(|e| {
     //     eprintln!("Error: {} at token {:?}", e.message, e.token);
@@ -974,7 +1429,7 @@ fn test_if_expresssion() {
                     left: Some(Box::new(Node::Identifier {
                         name: "x".to_string()
                     })),
-                    operator: "<".to_string(),
+                    operator: Operator::LessThan,
                     right: Some(Box::new(Node::Identifier {
                         name: "y".to_string()
                     })),
@@ -1001,7 +1456,7 @@ fn test_if_else_expresssion() {
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     // errors.clone().into_iter().for_each(|e| {
     //     eprintln!("Error: {} at token {:?}", e.message, e.token);
@@ -1022,7 +1477,7 @@ fn test_if_else_expresssion() {
                     left: Some(Box::new(Node::Identifier {
                         name: "x".to_string()
                     })),
-                    operator: "<".to_string(),
+                    operator: Operator::LessThan,
                     right: Some(Box::new(Node::Identifier {
                         name: "y".to_string()
                     })),
@@ -1057,7 +1512,7 @@ fn test_parse_function_literal(#[case] input: &str, #[case] expected_params: Vec
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     // errors.clone().into_iter().for_each(|e| {
     //     eprintln!("Error: {} at token {:?}", e.message, e.token);
@@ -1113,7 +1568,7 @@ fn test_parse_function_literal(#[case] input: &str, #[case] expected_params: Vec
                                 left: Some(Box::new(Node::Identifier {
                                     name: "x".to_string()
                                 })),
-                                operator: "+".to_string(),
+                                operator: Operator::Plus,
                                 right: Some(Box::new(Node::Identifier {
                                     name: "y".to_string()
                                 })),
@@ -1138,7 +1593,7 @@ fn test_call_expresssion() {
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     // errors.clone().into_iter().for_each(|e| {
     //     eprintln!("Error: {} at token {:?}", e.message, e.token);
@@ -1155,21 +1610,11 @@ fn test_call_expresssion() {
         statements.first(),
         Some(&Node::ExprStmt {
             expression: Some(Box::new(Node::Call {
-                function: Some(Box::new(Node::Identifier {
-                    name: "add".to_string(),
-                })),
+                function: Some(Box::new(ident!("add"))),
                 arguments: vec![
-                    Node::IntegerLiteral { value: 1 },
-                    Node::Infix {
-                        left: Some(Box::new(Node::IntegerLiteral { value: 2 })),
-                        operator: "*".to_string(),
-                        right: Some(Box::new(Node::IntegerLiteral { value: 3 })),
-                    },
-                    Node::Infix {
-                        left: Some(Box::new(Node::IntegerLiteral { value: 4 })),
-                        operator: "+".to_string(),
-                        right: Some(Box::new(Node::IntegerLiteral { value: 5 })),
-                    },
+                    int!(1),
+                    infix!("*", int!(2), int!(3)),
+                    infix!("+", int!(4), int!(5)),
                 ],
             }))
         })
@@ -1185,7 +1630,7 @@ fn test_let_function_function_assignment() {
     let mut parser = Parser::new(&mut lexer);
     let program = parser.parse_program();
 
-    let errors = parser.errors.into_iter();
+    let errors = parser.take_errors().into_iter();
 
     let statements = match &program {
         Node::Program { statements, .. } => statements,
@@ -1202,6 +1647,7 @@ fn test_let_function_function_assignment() {
             name: Some(Box::new(Node::Identifier {
                 name: "a".to_string()
             })),
+            type_annotation: None,
             value: Some(Box::new(Node::Function {
                 parameters: vec![
                     Node::Identifier {
@@ -1217,7 +1663,7 @@ fn test_let_function_function_assignment() {
                             left: Some(Box::new(Node::Identifier {
                                 name: "x".to_string()
                             })),
-                            operator: "+".to_string(),
+                            operator: Operator::Plus,
                             right: Some(Box::new(Node::Identifier {
                                 name: "y".to_string()
                             })),
@@ -1230,3 +1676,672 @@ fn test_let_function_function_assignment() {
 
     assert_eq!(program.string(), "let a = fn(x, y) {(x + y)}");
 }
+
+#[traced_test]
+#[rstest]
+#[case("x = 5", "(x = 5)")]
+#[case("x = 1 + 2", "(x = (1 + 2))")]
+#[case("a = b = 1", "(a = (b = 1))")]
+#[case("a = b == c", "(a = (b == c))")]
+#[case("let a = b = 1;", "let a = (b = 1)")]
+fn test_assignment_parsing(#[case] input: &str, #[case] expected_output: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 0);
+    assert_eq!(program.string(), expected_output);
+}
+
+#[traced_test]
+#[rstest]
+#[case("1 = 2")]
+#[case("a + b = 2")]
+fn test_invalid_assignment_target(#[case] input: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 1);
+    assert_eq!(parser.errors()[0].message, "Invalid assignment target");
+}
+
+#[rstest]
+fn test_parse_returns_all_errors() {
+    let input = "
+        let x 5;
+        let = 10;
+        ";
+
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+
+    let errors = parser.parse().expect_err("expected parse errors");
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].message, "Expected '=' after variable name");
+    assert_eq!(errors[1].message, "Expected identifier after 'let'");
+}
+
+#[rstest]
+fn test_parse_returns_program_without_errors() {
+    let mut lexer = Lexer::new("let x = 5;");
+    let mut parser = Parser::new(&mut lexer);
+
+    let program = parser.parse().expect("expected a program");
+
+    assert_eq!(program.string(), "let x = 5");
+}
+
+#[rstest]
+#[case("5 - -3", "(5 - (-3))")]
+#[case("5--3", "(5 - (-3))")]
+#[case("--5", "(-(-5))")]
+#[case("- - 5", "(-(-5))")]
+#[case("-(-5)", "(-(-5))")]
+#[case("-5 - -5", "((-5) - (-5))")]
+fn test_stacked_minus_parsing(#[case] input: &str, #[case] expected_output: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 0);
+    assert_eq!(program.string(), expected_output);
+}
+
+#[rstest]
+fn test_deeply_nested_parentheses_report_error() {
+    let input = format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000));
+
+    let mut lexer = Lexer::new(&input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 1);
+    assert_eq!(parser.errors()[0].message, "expression nesting too deep");
+}
+
+#[rstest]
+#[traced_test]
+fn test_long_flat_chain_parses_without_nesting() {
+    let input = vec!["1"; 10_000].join(" + ");
+
+    let mut lexer = Lexer::new(&input);
+    let mut parser = Parser::new(&mut lexer).with_max_depth(3);
+    let program = parser.parse_program();
+
+    assert!(parser.errors().is_empty());
+    assert!(matches!(program, Node::Program { ref statements } if statements.len() == 1));
+}
+
+#[rstest]
+#[case("((1))", false)]
+#[case("(((1)))", true)]
+#[case("--1", false)]
+#[case("---1", true)]
+fn test_max_depth_is_configurable(#[case] input: &str, #[case] too_deep: bool) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer).with_max_depth(3);
+    parser.parse_program();
+
+    let reported = parser
+        .errors()
+        .iter()
+        .any(|e| e.message == "expression nesting too deep");
+    assert_eq!(reported, too_deep);
+}
+
+#[rstest]
+#[case("'a'", "'a'")]
+#[case("'\\n'", "'\\n'")]
+#[case("'a' + 1", "('a' + 1)")]
+#[case("char(1 + int('a'))", "char((1 + int('a')))")]
+fn test_char_literal_parsing(#[case] input: &str, #[case] expected_output: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 0);
+    assert_eq!(program.string(), expected_output);
+}
+
+#[rstest]
+#[case("fn x {}", "Expected '(' after 'fn'")]
+#[case("fn() 5", "Expected '{' after function parameters")]
+#[case("fn(x) x", "Expected '{' after function parameters")]
+fn test_malformed_function_literal(#[case] input: &str, #[case] expected_message: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    assert_eq!(
+        parser.errors().first().map(|e| e.message.as_str()),
+        Some(expected_message)
+    );
+}
+
+#[rstest]
+#[case("fn() { 5 }", "fn() {5}")]
+#[case("fn() { let x = 1; x }", "fn() {let x = 1\nx}")]
+#[case("let f = fn() { 5 }; f", "let f = fn() {5}f")]
+fn test_parameterless_function_keeps_body(#[case] input: &str, #[case] expected_output: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 0);
+    assert_eq!(program.string(), expected_output);
+}
+
+#[rstest]
+#[case("let x: int = 5;", "let x: int = 5", Some("int"))]
+#[case("let f: fn = fn(x) { x };", "let f: fn = fn(x) {x}", Some("fn"))]
+#[case("let c: char = 'c';", "let c: char = 'c'", Some("char"))]
+#[case("let x = 5;", "let x = 5", None)]
+fn test_let_type_annotation(
+    #[case] input: &str,
+    #[case] expected_output: &str,
+    #[case] expected_annotation: Option<&str>,
+) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 0);
+    assert_eq!(program.string(), expected_output);
+
+    let statements = match program {
+        Node::Program { statements } => statements,
+        _ => panic!("Expected a program node"),
+    };
+    match statements.first() {
+        Some(Node::Let {
+            type_annotation, ..
+        }) => assert_eq!(type_annotation.as_deref(), expected_annotation),
+        other => panic!("Expected a let statement, got {:?}", other),
+    }
+}
+
+#[rstest]
+#[case("let x: integer = 5;", "Unknown type annotation 'integer'")]
+#[case("let x: = 5;", "Expected type name after ':'")]
+#[case("let x: int 5;", "Expected '=' after variable name")]
+fn test_broken_let_type_annotation(#[case] input: &str, #[case] expected_message: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    assert_eq!(
+        parser.errors().first().map(|e| e.message.as_str()),
+        Some(expected_message)
+    );
+}
+
+#[rstest]
+#[case("let x = 5\nlet y = 10\nx + y", "let x = 5let y = 10(x + y)")]
+#[case("let x = 5\n\n\n  x", "let x = 5x")]
+#[case("1 +\n2\n3", "(1 + 2)3")]
+#[case("let x =\n  5\nx", "let x = 5x")]
+#[case("add(1,\n  2)\n(1\n + 2)", "add(1, 2)(1 + 2)")]
+#[case(
+    "let f = fn(x) {\n  let y = x\n  return y\n}\nf(1)",
+    "let f = fn(x) {let y = x\nreturn y}f(1)"
+)]
+#[case("if (x) {\n  1\n} else {\n  2\n}\n", "if x 1 else 2")]
+#[case("if (x) {\n  1\n}\nelse {\n  2\n}", "if x 1 else 2")]
+#[case("if (x) { 1 }\n\n  else { 2 }\nx", "if x 1 else 2x")]
+#[case("let f = fn(x)\n{ x }\nf(1)", "let f = fn(x) {x}f(1)")]
+#[case("let a = 1; let b = 2;\nreturn a", "let a = 1let b = 2return a")]
+fn test_newline_terminated_statements(#[case] input: &str, #[case] expected_output: &str) {
+    let mut lexer = Lexer::new(input).with_newline_terminators(true);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 0, "errors: {:?}", parser.errors());
+    assert_eq!(program.string(), expected_output);
+}
+
+#[rstest]
+fn test_parser_follows_precedence_of() {
+    let operators = [
+        Token::Plus,
+        Token::Minus,
+        Token::Asterisk,
+        Token::Slash,
+        Token::Percent,
+        Token::LessThan,
+        Token::GreaterThan,
+        Token::LessEq,
+        Token::GreaterEq,
+        Token::Eq,
+        Token::NotEq,
+        Token::And,
+        Token::Or,
+    ];
+
+    for first in &operators {
+        for second in &operators {
+            let input = format!("a {} b {} c", first.to_literal(), second.to_literal());
+            let mut lexer = Lexer::new(&input);
+            let mut parser = Parser::new(&mut lexer);
+            let program = parser.parse_program();
+
+            let expected = if precedence_of(first) >= precedence_of(second) {
+                format!("((a {} b) {} c)", first.to_literal(), second.to_literal())
+            } else {
+                format!("(a {} (b {} c))", first.to_literal(), second.to_literal())
+            };
+            assert_eq!(program.string(), expected, "input: {}", input);
+        }
+    }
+}
+
+#[rstest]
+#[case(
+    "99999999999999999999;",
+    "integer literal 99999999999999999999 is out of range for i64"
+)]
+#[case(
+    "let x = 1 + 9223372036854775808;",
+    "integer literal 9223372036854775808 is out of range for i64"
+)]
+fn test_integer_literal_out_of_range(#[case] input: &str, #[case] expected_message: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    assert_eq!(
+        parser.errors().first().map(|e| e.message.as_str()),
+        Some(expected_message)
+    );
+}
+
+#[rstest]
+#[case("let x = return 5;")]
+#[case("let x = return;")]
+#[case("foo(return 1);")]
+#[case("let f = fn() { let y = return 2 * 3; y };")]
+fn test_return_in_expression_position(#[case] input: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    assert_eq!(
+        parser.errors().first().map(|e| e.message.as_str()),
+        Some("'return' is a statement and cannot be used as an expression"),
+        "input: {}",
+        input
+    );
+}
+
+#[rstest]
+fn test_return_in_expression_position_reports_one_error() {
+    let mut lexer = Lexer::new("let x = return 5; x;");
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 1);
+    assert_eq!(program.string(), "let x = x");
+}
+
+#[rstest]
+fn test_parser_reset_reuses_parser() {
+    let mut lexer = Lexer::new("let = 5;");
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+    assert!(!parser.errors().is_empty());
+
+    parser.reset("let x = 1 + 2; x");
+    let program = parser.parse_program();
+
+    assert!(parser.errors().is_empty());
+    assert_eq!(program.string(), "let x = (1 + 2)x");
+}
+
+#[rstest]
+#[case("let x = 5", "let x = 5")]
+#[case("let x = 5\nlet y = 10", "let x = 5let y = 10")]
+#[case("return 5", "return 5")]
+#[case("return", "return ")]
+#[case("return;", "return ")]
+#[case("return 1\nreturn 2;", "return 1return 2")]
+#[case("fn() { return }", "fn() {return }")]
+#[case("fn() { let x = 1\n x }", "fn() {let x = 1\nx}")]
+#[case("fn() { let x = 1 }", "fn() {let x = 1}")]
+#[case("let a = 1, b = 2\nreturn a", "let a = 1let b = 2return a")]
+fn test_optional_semicolons(#[case] input: &str, #[case] expected_output: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 0, "errors: {:?}", parser.errors());
+    assert_eq!(program.string(), expected_output);
+}
+
+#[rstest]
+#[case(
+    "let x = 5 let y = x; y",
+    "Expected ';' or a line break after 'let' statement",
+    1,
+    11
+)]
+#[case(
+    "let a = 1, b = 2 b",
+    "Expected ';' or a line break after 'let' statement",
+    1,
+    18
+)]
+#[case(
+    "fn() { return 1 2 }",
+    "Expected ';' or a line break after 'return' statement",
+    1,
+    17
+)]
+#[case(
+    "let x = 1;\nlet y = x x",
+    "Expected ';' or a line break after 'let' statement",
+    2,
+    11
+)]
+fn test_missing_terminator_mid_line(
+    #[case] input: &str,
+    #[case] expected_message: &str,
+    #[case] line: usize,
+    #[case] column: usize,
+) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    let error = parser.errors().first().expect("expected a parse error");
+    assert_eq!(error.message, expected_message);
+    assert_eq!(error.position, Position { line, column });
+}
+
+#[rstest]
+#[case("add(1)(2)", "add(1)(2)")]
+#[case("f(n - 1) + x", "(f((n - 1)) + x)")]
+#[case("f(g(x))", "f(g(x))")]
+#[case("f(a, b)(c)(d, e)", "f(a, b)(c)(d, e)")]
+#[case("f() + g()", "(f() + g())")]
+#[case("-f(1) * 2", "((-f(1)) * 2)")]
+fn test_call_expression_parsing(#[case] input: &str, #[case] expected_output: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 0, "errors: {:?}", parser.errors());
+    assert_eq!(program.string(), expected_output);
+}
+
+#[rstest]
+fn test_unclosed_call_arguments() {
+    let mut lexer = Lexer::new("add(1, 2");
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    assert_eq!(
+        parser.errors().first().map(|e| e.message.as_str()),
+        Some("Expected ')' after call arguments")
+    );
+}
+
+#[rstest]
+fn test_error_accessors() {
+    let mut lexer = Lexer::new("let = 1; let x 2;");
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    let messages: Vec<String> = parser.errors().iter().map(|e| e.message.clone()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Expected identifier after 'let'".to_string(),
+            "Expected '=' after variable name".to_string(),
+        ]
+    );
+
+    let taken = parser.take_errors();
+    assert_eq!(taken.len(), 2);
+    assert!(parser.errors().is_empty());
+}
+
+/// Whether the infix loop in `parse_nested_expression` parses `token`,
+/// mirroring its dispatch.
+#[cfg(test)]
+fn has_infix_handler(token: &Token) -> bool {
+    use crate::token::Token::*;
+    matches!(
+        token,
+        Lparen
+            | Assign
+            | Plus
+            | Minus
+            | Slash
+            | Percent
+            | Asterisk
+            | Eq
+            | NotEq
+            | LessThan
+            | GreaterThan
+            | LessEq
+            | GreaterEq
+            | And
+            | Or
+    )
+}
+
+#[rstest]
+#[case(Token::Lparen, Precedence::Call)]
+#[case(Token::Assign, Precedence::Assign)]
+#[case(Token::Or, Precedence::Or)]
+#[case(Token::And, Precedence::And)]
+#[case(Token::Eq, Precedence::Equals)]
+#[case(Token::NotEq, Precedence::Equals)]
+#[case(Token::LessThan, Precedence::LessGreater)]
+#[case(Token::GreaterThan, Precedence::LessGreater)]
+#[case(Token::LessEq, Precedence::LessGreater)]
+#[case(Token::GreaterEq, Precedence::LessGreater)]
+#[case(Token::Plus, Precedence::Sum)]
+#[case(Token::Minus, Precedence::Sum)]
+#[case(Token::Asterisk, Precedence::Product)]
+#[case(Token::Slash, Precedence::Product)]
+#[case(Token::Percent, Precedence::Product)]
+#[case(Token::Bang, Precedence::Lowest)]
+#[case(Token::FatArrow, Precedence::Lowest)]
+#[case(Token::Comma, Precedence::Lowest)]
+#[case(Token::Semicolon, Precedence::Lowest)]
+#[case(Token::Newline, Precedence::Lowest)]
+#[case(Token::Rparen, Precedence::Lowest)]
+#[case(Token::Lbrace, Precedence::Lowest)]
+#[case(Token::Ident("x".to_string()), Precedence::Lowest)]
+#[case(Token::Int(1), Precedence::Lowest)]
+#[case(Token::Eof, Precedence::Lowest)]
+fn test_tokens_with_precedence_have_infix_handler(
+    #[case] token: Token,
+    #[case] expected: Precedence,
+) {
+    assert_eq!(precedence_of(&token), expected);
+    assert_eq!(
+        has_infix_handler(&token),
+        expected != Precedence::Lowest,
+        "{} has a precedence but is not wired into the parser",
+        token
+    );
+}
+
+#[rstest]
+#[case(
+    "match x { 1 => 10, 2 => 20, _ => 0 }",
+    "match x { 1 => 10, 2 => 20, _ => 0 }"
+)]
+#[case("match x { 1 => 10, }", "match x { 1 => 10 }")]
+#[case("match x {}", "match x {}")]
+#[case(
+    "match f(x) + 1 { -1 => a * 2, _ => 'c' }",
+    "match (f(x) + 1) { (-1) => (a * 2), _ => 'c' }"
+)]
+#[case("let y = match x { true => 1 };", "let y = match x { true => 1 }")]
+#[case("match x {\n  1 => 10,\n  _ => 0\n}", "match x { 1 => 10, _ => 0 }")]
+fn test_parse_match_expression(#[case] input: &str, #[case] expected: &str) {
+    let mut lexer = Lexer::new(input).with_newline_terminators(true);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+    assert_eq!(program.string(), expected);
+}
+
+#[rstest]
+#[case("match x 1 => 2", "Expected '{' after match subject")]
+#[case("match x { 1 2 }", "Expected '=>' after match pattern")]
+#[case("match x { 1 => }", "Expected expression after '=>'")]
+#[case("match x { => 1 }", "Expected pattern in match arm")]
+#[case("match x { 1 => 2 3 => 4 }", "Expected ',' or '}' after match arm")]
+#[case("match x { _ => 1, 2 => 3 }", "Unreachable match arm after '_'")]
+fn test_parse_match_expression_errors(#[case] input: &str, #[case] expected_message: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    assert_eq!(
+        parser.errors().first().map(|e| e.message.as_str()),
+        Some(expected_message)
+    );
+}
+
+#[rstest]
+#[case("fn() {}", 0)]
+#[case("fn(){}", 0)]
+#[case("fn(x, y) {\n}", 2)]
+fn test_parse_empty_function_body(#[case] input: &str, #[case] parameter_count: usize) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert!(parser.errors().is_empty());
+    let Node::Program { statements } = &program else {
+        panic!("Expected a program node");
+    };
+    let Some(Node::ExprStmt {
+        expression: Some(function),
+    }) = statements.first()
+    else {
+        panic!("Expected an expression statement");
+    };
+    let Node::Function { parameters, body } = function.as_ref() else {
+        panic!("Expected a function literal, got {:?}", function);
+    };
+    assert_eq!(parameters.len(), parameter_count);
+    assert_eq!(body.as_deref(), Some(&Node::Block { statements: vec![] }));
+    assert!(function.string().ends_with(") {}"));
+}
+
+#[rstest]
+#[case("let a = 1, b = 2;", "let a = 1let b = 2")]
+#[case("let a = 1, b = a + 1;", "let a = 1let b = (a + 1)")]
+#[case("let f = add(1, 2), g: int = 3", "let f = add(1, 2)let g: int = 3")]
+#[case("let a = 1,\n    b = 2\na", "let a = 1let b = 2a")]
+fn test_parse_let_multiple_bindings(#[case] input: &str, #[case] expected: &str) {
+    let mut lexer = Lexer::new(input).with_newline_terminators(true);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+    assert_eq!(program.string(), expected);
+}
+
+#[rstest]
+#[case("let a = 1, ;", "Expected identifier after ','")]
+#[case("let a = 1, 2;", "Expected identifier after ','")]
+#[case("let a = 1, b 2;", "Expected '=' after variable name")]
+fn test_parse_let_multiple_bindings_errors(#[case] input: &str, #[case] expected_message: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    assert_eq!(
+        parser.errors().first().map(|e| e.message.as_str()),
+        Some(expected_message)
+    );
+}
+
+#[rstest]
+fn test_parse_errors_carry_positions() {
+    let input = "let a = 1;\nlet b = 2;\n    let total 3;\n";
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    let error = parser.errors().first().expect("expected a parse error");
+    assert_eq!(error.token, Token::Ident("total".to_string()));
+    assert_eq!(error.position, Position { line: 3, column: 9 });
+    assert_eq!(format!("error at {}", error.position), "error at 3:9");
+}
+
+#[rstest]
+fn test_illegal_character_is_reported_at_its_position() {
+    let input = "let a = 1;\nlet b = 2;\nlet total = a @ b;\n";
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    let error = parser.errors().first().expect("expected a parse error");
+    assert_eq!(error.message, "unexpected character '@'");
+    assert_eq!(error.token, Token::Illegal("@".to_string()));
+    assert_eq!(
+        error.position,
+        Position {
+            line: 3,
+            column: 15
+        }
+    );
+}
+
+#[rstest]
+#[case("x @ 1", "unexpected character '@'")]
+#[case("let c = '';", "malformed literal ''")]
+#[case("let c = 'ab'; c", "malformed literal 'ab'")]
+#[case("let s = \"open;", "malformed literal \"open;")]
+fn test_illegal_tokens_are_parse_errors(#[case] input: &str, #[case] expected: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    let messages: Vec<&str> = parser.errors().iter().map(|e| e.message.as_str()).collect();
+    assert!(messages.contains(&expected), "messages: {:?}", messages);
+}
+
+#[rstest]
+#[case("let x = 3.14; x", "float literals are not supported yet")]
+#[case("1 + 2.5", "float literals are not supported yet")]
+#[case("let s = \"hi\"; s", "string literals are not supported yet")]
+fn test_unsupported_literals_are_parse_errors(#[case] input: &str, #[case] expected: &str) {
+    let mut lexer = Lexer::new(input);
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_program();
+
+    assert_eq!(
+        parser.errors().first().map(|e| e.message.as_str()),
+        Some(expected),
+        "input: {}",
+        input
+    );
+}
+
+#[rstest]
+fn test_newline_after_if_without_else_ends_the_statement() {
+    let mut lexer = Lexer::new("if (x) { 1 }\n(2)").with_newline_terminators(true);
+    let mut parser = Parser::new(&mut lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(parser.errors().len(), 0, "errors: {:?}", parser.errors());
+    let Node::Program { statements } = program else {
+        unreachable!()
+    };
+    assert_eq!(statements.len(), 2);
+    assert!(matches!(
+        &statements[0],
+        Node::ExprStmt { expression: Some(e) } if matches!(**e, Node::If { alternative: None, .. })
+    ));
+}
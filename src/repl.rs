@@ -0,0 +1,245 @@
+use rstest::rstest;
+use rustyline::DefaultEditor;
+use rustyline::error::ReadlineError;
+
+use crate::ast::Node;
+use crate::environment::Env;
+use crate::evaluator::Evaluator;
+use crate::lexer::Lexer;
+use crate::object::{Category, Object};
+use crate::parser::Parser;
+use crate::token::Token;
+
+/// Text and behaviour of an interactive session started with [`run_repl`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplOptions {
+    /// Printed once before the first prompt.
+    pub banner: String,
+    pub prompt: String,
+    /// Shown instead of `prompt` while brackets opened on earlier lines are unclosed.
+    pub continuation_prompt: String,
+    /// Print the value of every evaluated input.
+    pub echo_results: bool,
+    /// Let line breaks end statements, so `let x = 5` needs no trailing `;`.
+    pub newline_terminators: bool,
+    /// Highlight echoed errors in red and null in grey. Off by default when `NO_COLOR` is set.
+    pub color: bool,
+}
+
+impl Default for ReplOptions {
+    fn default() -> Self {
+        ReplOptions {
+            banner:
+                "Hello! This is the Roadrunner programming language!\nFeel free to type in commands"
+                    .to_string(),
+            prompt: "⚡: ".to_string(),
+            continuation_prompt: "... ".to_string(),
+            echo_results: true,
+            newline_terminators: true,
+            color: colors_enabled(std::env::var_os("NO_COLOR")),
+        }
+    }
+}
+
+impl ReplOptions {
+    /// The prompt to show, depending on whether earlier input is still incomplete.
+    pub fn prompt_for(&self, continuing: bool) -> &str {
+        if continuing {
+            &self.continuation_prompt
+        } else {
+            &self.prompt
+        }
+    }
+}
+
+/// The evaluator and environment that a REPL session's inputs share.
+struct Session {
+    evaluator: Evaluator,
+    environment: Env,
+}
+
+impl Session {
+    fn new(evaluator: Evaluator) -> Self {
+        let environment = evaluator.environment();
+        Session {
+            evaluator,
+            environment,
+        }
+    }
+
+    fn eval(&self, program: Node) -> Object {
+        self.evaluator.eval(program, self.environment.clone())
+    }
+
+    /// Swaps in a fresh environment (with the prelude, if the evaluator loads one),
+    /// dropping every binding made so far.
+    fn reset(&mut self) {
+        self.environment = self.evaluator.environment();
+    }
+}
+
+/// Runs an interactive read-eval-print loop until the user exits with CTRL-C or CTRL-D.
+pub fn run_repl(options: &ReplOptions) -> Result<(), anyhow::Error> {
+    println!("{}", options.banner);
+
+    let mut rl = DefaultEditor::new()?;
+
+    let mut session = Session::new(Evaluator::new());
+    let mut pending = String::new();
+
+    loop {
+        tracing::debug!("Awaiting user input...");
+        let readline = rl.readline(options.prompt_for(!pending.is_empty()));
+        match readline {
+            Ok(line) => {
+                if pending.is_empty() && line.trim() == ":reset" {
+                    session.reset();
+                    println!("Environment reset.");
+                    continue;
+                }
+
+                pending.push_str(&line);
+                pending.push('\n');
+
+                if is_incomplete(&pending) {
+                    continue;
+                }
+                let input = std::mem::take(&mut pending);
+
+                let mut lexer =
+                    Lexer::new(&input).with_newline_terminators(options.newline_terminators);
+                let mut parser = Parser::new(&mut lexer);
+                tracing::debug!("Parsing program with input: {:?}", input);
+                let program = parser.parse_program();
+
+                if !parser.errors().is_empty() {
+                    tracing::error!("Parser errors encountered: {:?}", parser.errors());
+                    for err in parser.errors().iter() {
+                        println!("\terror at {}: {}", err.position, err.message);
+                    }
+                }
+
+                let evaluated = session.eval(program);
+
+                if options.echo_results {
+                    println!("{}", format_result(&evaluated, options.color));
+                }
+            }
+            Err(ReadlineError::Interrupted) => {
+                println!("CTRL-C pressed. Exiting.");
+                break;
+            }
+            Err(ReadlineError::Eof) => {
+                println!("CTRL-D pressed. Exiting.");
+                break;
+            }
+            Err(err) => {
+                println!("Error: {:?}", err);
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether to use ANSI colors given the value of the `NO_COLOR` environment variable,
+/// which disables them when set to anything non-empty (see no-color.org).
+fn colors_enabled(no_color: Option<std::ffi::OsString>) -> bool {
+    no_color.is_none_or(|value| value.is_empty())
+}
+
+/// Renders an evaluated result for echoing, colored by its display category.
+fn format_result(result: &Object, color: bool) -> String {
+    if !color {
+        return result.to_string();
+    }
+    let code = match result.display_category() {
+        Category::Value => return result.to_string(),
+        Category::Error => "31",
+        Category::Null => "90",
+    };
+    format!("\x1b[{}m{}\x1b[0m", code, result)
+}
+
+/// Whether `input` has more opening than closing parentheses or braces.
+fn is_incomplete(input: &str) -> bool {
+    let depth = Lexer::new(input).fold(0i64, |depth, token| match token {
+        Token::Lparen | Token::Lbrace => depth + 1,
+        Token::Rparen | Token::Rbrace => depth - 1,
+        _ => depth,
+    });
+    depth > 0
+}
+
+#[rstest]
+fn test_default_prompts() {
+    let options = ReplOptions::default();
+
+    assert_eq!(options.prompt_for(false), "⚡: ");
+    assert_eq!(options.prompt_for(true), "... ");
+    assert!(options.echo_results);
+    assert!(options.newline_terminators);
+}
+
+#[rstest]
+fn test_custom_prompts() {
+    let options = ReplOptions {
+        banner: "Welcome to Acme Script".to_string(),
+        prompt: "acme> ".to_string(),
+        continuation_prompt: "acme| ".to_string(),
+        echo_results: false,
+        newline_terminators: false,
+        color: false,
+    };
+
+    assert_eq!(options.prompt_for(false), "acme> ");
+    assert_eq!(options.prompt_for(true), "acme| ");
+}
+
+#[rstest]
+#[case("let x = 5;", false)]
+#[case("let f = fn(x) {", true)]
+#[case("let f = fn(x) {\n x\n};", false)]
+#[case("add(1,", true)]
+fn test_is_incomplete(#[case] input: &str, #[case] expected: bool) {
+    assert_eq!(is_incomplete(input), expected);
+}
+
+#[rstest]
+fn test_session_reset_drops_bindings() {
+    let eval_input = |session: &Session, input: &str| {
+        let mut lexer = Lexer::new(input);
+        session.eval(Parser::new(&mut lexer).parse_program())
+    };
+    let mut session = Session::new(Evaluator::new());
+
+    eval_input(&session, "let x = 5;");
+    assert_eq!(eval_input(&session, "x"), Object::Integer(5));
+
+    session.reset();
+
+    assert_eq!(
+        eval_input(&session, "x"),
+        Object::Error("identifier not found: x".to_string())
+    );
+    assert_eq!(eval_input(&session, "identity(7)"), Object::Integer(7));
+}
+
+#[rstest]
+#[case(None, true)]
+#[case(Some(""), true)]
+#[case(Some("1"), false)]
+fn test_no_color_disables_colors(#[case] no_color: Option<&str>, #[case] expected: bool) {
+    assert_eq!(colors_enabled(no_color.map(Into::into)), expected);
+}
+
+#[rstest]
+#[case(Object::Integer(5), true, "5")]
+#[case(Object::Error("boom".to_string()), true, "\x1b[31mboom\x1b[0m")]
+#[case(Object::Null, true, "\x1b[90mnull\x1b[0m")]
+#[case(Object::Error("boom".to_string()), false, "boom")]
+#[case(Object::Null, false, "null")]
+fn test_format_result(#[case] result: Object, #[case] color: bool, #[case] expected: &str) {
+    assert_eq!(format_result(&result, color), expected);
+}
@@ -0,0 +1,161 @@
+// Shorthand for building `Node` trees in tests: `infix!("+", int!(1), ident!("x"))`
+// expands to the nested `Node::Infix { left: Some(Box::new(...)), ... }` literal.
+// Operators are matched at compile time, so an unknown symbol fails the build.
+
+use rstest::rstest;
+
+use crate::ast::{Node, Operator};
+
+/// `int!(5)` is `Node::IntegerLiteral { value: 5 }`.
+macro_rules! int {
+    ($value:expr) => {
+        $crate::ast::Node::IntegerLiteral { value: $value }
+    };
+}
+
+/// `ident!("x")` is `Node::Identifier { name: "x".to_string() }`.
+macro_rules! ident {
+    ($name:expr) => {
+        $crate::ast::Node::Identifier {
+            name: ($name).to_string(),
+        }
+    };
+}
+
+/// The `Operator` spelled by a string literal, e.g. `operator!("==")`.
+macro_rules! operator {
+    ("+") => {
+        $crate::ast::Operator::Plus
+    };
+    ("-") => {
+        $crate::ast::Operator::Minus
+    };
+    ("*") => {
+        $crate::ast::Operator::Asterisk
+    };
+    ("/") => {
+        $crate::ast::Operator::Slash
+    };
+    ("%") => {
+        $crate::ast::Operator::Percent
+    };
+    ("!") => {
+        $crate::ast::Operator::Bang
+    };
+    ("<") => {
+        $crate::ast::Operator::LessThan
+    };
+    (">") => {
+        $crate::ast::Operator::GreaterThan
+    };
+    ("<=") => {
+        $crate::ast::Operator::LessEq
+    };
+    (">=") => {
+        $crate::ast::Operator::GreaterEq
+    };
+    ("==") => {
+        $crate::ast::Operator::Eq
+    };
+    ("!=") => {
+        $crate::ast::Operator::NotEq
+    };
+    ("&&") => {
+        $crate::ast::Operator::And
+    };
+    ("||") => {
+        $crate::ast::Operator::Or
+    };
+}
+
+/// `infix!("+", left, right)` is a `Node::Infix` with both operands present.
+macro_rules! infix {
+    ($operator:tt, $left:expr, $right:expr) => {
+        $crate::ast::Node::Infix {
+            left: Some(Box::new($left)),
+            operator: $crate::test_support::operator!($operator),
+            right: Some(Box::new($right)),
+        }
+    };
+}
+
+/// `let_!("x", value)` is an unannotated `let x = value`.
+macro_rules! let_ {
+    ($name:expr, $value:expr) => {
+        $crate::ast::Node::Let {
+            name: Some(Box::new($crate::test_support::ident!($name))),
+            type_annotation: None,
+            value: Some(Box::new($value)),
+        }
+    };
+}
+
+pub(crate) use {ident, infix, int, let_, operator};
+
+#[rstest]
+fn test_leaf_macros() {
+    assert_eq!(int!(5), Node::IntegerLiteral { value: 5 });
+    assert_eq!(int!(-1), Node::IntegerLiteral { value: -1 });
+    assert_eq!(
+        ident!("x"),
+        Node::Identifier {
+            name: "x".to_string()
+        }
+    );
+}
+
+#[rstest]
+fn test_operator_macro() {
+    assert_eq!(operator!("+"), Operator::Plus);
+    assert_eq!(operator!("-"), Operator::Minus);
+    assert_eq!(operator!("*"), Operator::Asterisk);
+    assert_eq!(operator!("/"), Operator::Slash);
+    assert_eq!(operator!("%"), Operator::Percent);
+    assert_eq!(operator!("!"), Operator::Bang);
+    assert_eq!(operator!("<"), Operator::LessThan);
+    assert_eq!(operator!(">"), Operator::GreaterThan);
+    assert_eq!(operator!("<="), Operator::LessEq);
+    assert_eq!(operator!(">="), Operator::GreaterEq);
+    assert_eq!(operator!("=="), Operator::Eq);
+    assert_eq!(operator!("!="), Operator::NotEq);
+    assert_eq!(operator!("&&"), Operator::And);
+    assert_eq!(operator!("||"), Operator::Or);
+}
+
+#[rstest]
+fn test_infix_macro() {
+    assert_eq!(
+        infix!("*", int!(2), infix!("+", ident!("a"), int!(3))),
+        Node::Infix {
+            left: Some(Box::new(Node::IntegerLiteral { value: 2 })),
+            operator: Operator::Asterisk,
+            right: Some(Box::new(Node::Infix {
+                left: Some(Box::new(Node::Identifier {
+                    name: "a".to_string()
+                })),
+                operator: Operator::Plus,
+                right: Some(Box::new(Node::IntegerLiteral { value: 3 })),
+            })),
+        }
+    );
+}
+
+#[rstest]
+fn test_let_macro() {
+    let name = String::from("total");
+
+    assert_eq!(
+        let_!(name, infix!("-", int!(1), int!(2))),
+        Node::Let {
+            name: Some(Box::new(Node::Identifier {
+                name: "total".to_string()
+            })),
+            type_annotation: None,
+            value: Some(Box::new(Node::Infix {
+                left: Some(Box::new(Node::IntegerLiteral { value: 1 })),
+                operator: Operator::Minus,
+                right: Some(Box::new(Node::IntegerLiteral { value: 2 })),
+            })),
+        }
+    );
+}
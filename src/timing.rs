@@ -0,0 +1,78 @@
+use std::time::{Duration, Instant};
+
+use rstest::rstest;
+
+/// Wall-clock durations of the interpreter's phases for a single run.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PhaseTimings {
+    pub parse: Duration,
+    pub eval: Duration,
+}
+
+impl PhaseTimings {
+    /// Renders one `label: duration` line per phase.
+    pub fn report(&self) -> String {
+        format!("lex+parse: {:?}\neval: {:?}", self.parse, self.eval)
+    }
+}
+
+/// Runs `phase` and returns its result together with how long it took.
+pub fn time_phase<T>(phase: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = phase();
+    (result, start.elapsed())
+}
+
+/// Average of `samples`, or zero when there are none.
+pub fn mean(samples: &[Duration]) -> Duration {
+    match u32::try_from(samples.len()) {
+        Ok(0) | Err(_) => Duration::ZERO,
+        Ok(count) => samples.iter().sum::<Duration>() / count,
+    }
+}
+
+/// Middle value of `samples` (the mean of the two middle values for an even count).
+pub fn median(samples: &[Duration]) -> Duration {
+    let mut sorted = samples.to_vec();
+    sorted.sort();
+    match sorted.len() {
+        0 => Duration::ZERO,
+        len if len % 2 == 1 => sorted[len / 2],
+        len => mean(&sorted[len / 2 - 1..=len / 2]),
+    }
+}
+
+#[rstest]
+fn test_time_phase_returns_result() {
+    let (value, _) = time_phase(|| 40 + 2);
+    assert_eq!(value, 42);
+}
+
+#[rstest]
+fn test_report_contains_both_phases() {
+    let timings = PhaseTimings {
+        parse: Duration::from_millis(3),
+        eval: Duration::from_millis(7),
+    };
+
+    let report = timings.report();
+
+    assert!(report.contains("lex+parse: 3ms"));
+    assert!(report.contains("eval: 7ms"));
+}
+
+#[rstest]
+#[case(&[], Duration::ZERO, Duration::ZERO)]
+#[case(&[5], Duration::from_millis(5), Duration::from_millis(5))]
+#[case(&[9, 1, 2], Duration::from_millis(4), Duration::from_millis(2))]
+#[case(&[4, 1, 3, 8], Duration::from_millis(4), Duration::from_millis(3) + Duration::from_micros(500))]
+fn test_mean_and_median(
+    #[case] millis: &[u64],
+    #[case] expected_mean: Duration,
+    #[case] expected_median: Duration,
+) {
+    let samples: Vec<Duration> = millis.iter().map(|ms| Duration::from_millis(*ms)).collect();
+
+    assert_eq!(mean(&samples), expected_mean);
+    assert_eq!(median(&samples), expected_median);
+}
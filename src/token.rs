@@ -1,23 +1,40 @@
 use std::fmt::Display;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Token {
-    Illegal,
+    /// Source text the lexer couldn't make sense of: a stray character or a
+    /// malformed literal.
+    Illegal(String),
     Eof,
     Ident(String),
     Int(i64),
+    /// An integer literal too large for `i64`, keeping its source text for diagnostics.
+    IntOverflow(String),
+    Float(f64),
+    Char(char),
+    String(String),
     Assign,
+    /// `=>`, separating a match arm's pattern from its value.
+    FatArrow,
     Plus,
     Minus,
     Bang,
     Asterisk,
     Slash,
+    Percent,
     LessThan,
     GreaterThan,
+    LessEq,
+    GreaterEq,
+    And,
+    Or,
     Eq,
     NotEq,
     Comma,
+    Colon,
     Semicolon,
+    /// Only produced by lexers built with `Lexer::with_newline_terminators(true)`.
+    Newline,
     Lparen,
     Rparen,
     Lbrace,
@@ -29,27 +46,40 @@ pub enum Token {
     If,
     Else,
     Return,
+    Match,
 }
 
 impl Token {
     pub fn to_literal(&self) -> String {
         match self {
-            Token::Illegal => "ILLEGAL".to_string(),
+            Token::Illegal(text) => text.clone(),
             Token::Eof => "EOF".to_string(),
             Token::Ident(id) => id.clone().to_owned(),
             Token::Int(int) => int.to_string(),
+            Token::IntOverflow(literal) => literal.clone(),
+            Token::Float(value) => format!("{:?}", value),
+            Token::Char(c) => char_literal(*c),
+            Token::String(s) => string_literal(s),
             Token::Assign => "=".to_string(),
+            Token::FatArrow => "=>".to_string(),
             Token::Plus => "+".to_string(),
             Token::Minus => "-".to_string(),
             Token::Bang => "!".to_string(),
             Token::Asterisk => "*".to_string(),
             Token::Slash => "/".to_string(),
+            Token::Percent => "%".to_string(),
             Token::LessThan => "<".to_string(),
             Token::GreaterThan => ">".to_string(),
+            Token::LessEq => "<=".to_string(),
+            Token::GreaterEq => ">=".to_string(),
+            Token::And => "&&".to_string(),
+            Token::Or => "||".to_string(),
             Token::Eq => "==".to_string(),
             Token::NotEq => "!=".to_string(),
             Token::Comma => ",".to_string(),
+            Token::Colon => ":".to_string(),
             Token::Semicolon => ";".to_string(),
+            Token::Newline => "NEWLINE".to_string(),
             Token::Lparen => "(".to_string(),
             Token::Rparen => ")".to_string(),
             Token::Lbrace => "{".to_string(),
@@ -61,6 +91,7 @@ impl Token {
             Token::If => "if".to_string(),
             Token::Else => "else".to_string(),
             Token::Return => "return".to_string(),
+            Token::Match => "match".to_string(),
         }
     }
 }
@@ -68,22 +99,34 @@ impl Token {
 impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Token::Illegal => write!(f, "Illegal"),
+            Token::Illegal(text) => write!(f, "Illegal({})", text),
             Token::Eof => write!(f, "EOF"),
             Token::Ident(ident) => write!(f, "Ident({})", ident),
             Token::Int(value) => write!(f, "Int({})", value),
+            Token::IntOverflow(literal) => write!(f, "IntOverflow({})", literal),
+            Token::Float(value) => write!(f, "Float({:?})", value),
+            Token::Char(value) => write!(f, "Char({})", char_literal(*value)),
+            Token::String(value) => write!(f, "String({})", string_literal(value)),
             Token::Assign => write!(f, "="),
+            Token::FatArrow => write!(f, "=>"),
             Token::Plus => write!(f, "+"),
             Token::Minus => write!(f, "-"),
             Token::Bang => write!(f, "!"),
             Token::Asterisk => write!(f, "*"),
             Token::Slash => write!(f, "/"),
+            Token::Percent => write!(f, "%"),
             Token::LessThan => write!(f, "<"),
             Token::GreaterThan => write!(f, ">"),
+            Token::LessEq => write!(f, "<="),
+            Token::GreaterEq => write!(f, ">="),
+            Token::And => write!(f, "&&"),
+            Token::Or => write!(f, "||"),
             Token::Eq => write!(f, "=="),
             Token::NotEq => write!(f, "!="),
             Token::Comma => write!(f, ","),
+            Token::Colon => write!(f, ":"),
             Token::Semicolon => write!(f, ";"),
+            Token::Newline => write!(f, "NEWLINE"),
             Token::Lparen => write!(f, "("),
             Token::Rparen => write!(f, ")"),
             Token::Lbrace => write!(f, "{{"),
@@ -95,11 +138,56 @@ impl Display for Token {
             Token::If => write!(f, "if"),
             Token::Else => write!(f, "else"),
             Token::Return => write!(f, "return"),
+            Token::Match => write!(f, "match"),
         }
     }
 }
 
-pub const KEYWORDS: [(&str, Token); 7] = [
+/// Where a token starts in the source: a 1-based line and a 1-based column counted in chars.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Renders `c` as a single-quoted char literal, escaping the characters the lexer unescapes.
+pub fn char_literal(c: char) -> String {
+    match c {
+        '\n' => "'\\n'".to_string(),
+        '\t' => "'\\t'".to_string(),
+        '\r' => "'\\r'".to_string(),
+        '\0' => "'\\0'".to_string(),
+        '\\' => "'\\\\'".to_string(),
+        '\'' => "'\\''".to_string(),
+        _ => format!("'{}'", c),
+    }
+}
+
+/// Renders `s` as a double-quoted string literal, escaping the characters the lexer unescapes.
+pub fn string_literal(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub const KEYWORDS: [(&str, Token); 8] = [
     ("fn", Token::Function),
     ("let", Token::Let),
     ("true", Token::True),
@@ -107,6 +195,7 @@ pub const KEYWORDS: [(&str, Token); 7] = [
     ("if", Token::If),
     ("else", Token::Else),
     ("return", Token::Return),
+    ("match", Token::Match),
 ];
 
 pub fn lookup_ident(ident: &str) -> Token {
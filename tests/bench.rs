@@ -0,0 +1,19 @@
+use std::process::Command;
+
+use roadrunner::corpus::PROGRAMS;
+use rstest::rstest;
+
+#[rstest]
+fn test_bench_runs_every_program() {
+    let output = Command::new(env!("CARGO_BIN_EXE_bench"))
+        .arg("1")
+        .output()
+        .expect("failed to run binary");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout.lines().count(), PROGRAMS.len());
+    for program in PROGRAMS {
+        assert!(stdout.contains(program.name), "missing {}", program.name);
+    }
+}
@@ -0,0 +1,91 @@
+use std::process::Command;
+
+use rstest::rstest;
+
+mod common;
+
+use common::write_script;
+
+#[rstest]
+fn test_check_reports_syntax_errors() {
+    let path = write_script("check-errors", "let x 5;\nlet = 10;\nx;\n");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_run"))
+        .arg("--check")
+        .arg(&path)
+        .output()
+        .expect("failed to run binary");
+    std::fs::remove_file(&path).ok();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr.lines().count(), 2);
+    assert!(stderr.contains(":1:5: Expected '=' after variable name"));
+    assert!(stderr.contains(":2:5: Expected identifier after 'let'"));
+}
+
+#[rstest]
+fn test_check_does_not_evaluate() {
+    let path = write_script("check-ok", "let x = 5;\nundefined_name;\n");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_run"))
+        .arg("--check")
+        .arg(&path)
+        .output()
+        .expect("failed to run binary");
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+}
+
+#[rstest]
+fn test_check_reports_illegal_characters() {
+    let path = write_script("check-illegal", "let x = 1;\nlet y = x @ 2;\ny;\n");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_run"))
+        .arg("--check")
+        .arg(&path)
+        .output()
+        .expect("failed to run binary");
+    std::fs::remove_file(&path).ok();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr.contains(":2:11: unexpected character '@'"),
+        "stderr: {}",
+        stderr
+    );
+}
+
+#[rstest]
+#[case(
+    "float",
+    "let x = 3.14;\nx;\n",
+    ":1:9: float literals are not supported yet"
+)]
+#[case(
+    "string",
+    "let s = \"hi\";\ns;\n",
+    ":1:9: string literals are not supported yet"
+)]
+fn test_check_rejects_unsupported_literals(
+    #[case] name: &str,
+    #[case] source: &str,
+    #[case] expected: &str,
+) {
+    let path = write_script(&format!("check-{}", name), source);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_run"))
+        .arg("--check")
+        .arg(&path)
+        .output()
+        .expect("failed to run binary");
+    std::fs::remove_file(&path).ok();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains(expected), "stderr: {}", stderr);
+}
@@ -0,0 +1,8 @@
+use std::path::PathBuf;
+
+/// Writes `source` to a per-process temp file named after `name` and returns its path.
+pub fn write_script(name: &str, source: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("roadrunner-{}-{}.rr", name, std::process::id()));
+    std::fs::write(&path, source).expect("failed to write script");
+    path
+}
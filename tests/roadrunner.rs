@@ -0,0 +1,45 @@
+use std::process::Command;
+
+use rstest::rstest;
+
+mod common;
+
+use common::write_script;
+
+#[rstest]
+#[case("run", "let add = fn(a, b) { a + b };\nadd(2, 3);\n", Some(0), "5\n")]
+#[case("run", "missing;\n", Some(1), "identifier not found: missing\n")]
+#[case("check", "let x = 5;\nmissing;\n", Some(0), "")]
+#[case("check", "let x 5;\n", Some(1), "")]
+fn test_subcommands(
+    #[case] command: &str,
+    #[case] source: &str,
+    #[case] expected_code: Option<i32>,
+    #[case] expected_stdout: &str,
+) {
+    let path = write_script(
+        &format!("subcommand-{}-{}", command, expected_code.unwrap_or(-1)),
+        source,
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_roadrunner"))
+        .arg(command)
+        .arg(&path)
+        .output()
+        .expect("failed to run binary");
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(output.status.code(), expected_code);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected_stdout);
+}
+
+#[rstest]
+fn test_unknown_subcommand_prints_usage() {
+    let output = Command::new(env!("CARGO_BIN_EXE_roadrunner"))
+        .arg("fly")
+        .output()
+        .expect("failed to run binary");
+
+    assert_ne!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("usage: roadrunner"));
+}